@@ -0,0 +1,419 @@
+use chrono::Local;
+use iced::widget::button;
+use iced::widget::checkbox;
+use iced::widget::pick_list;
+use iced::widget::row;
+use iced::widget::scrollable;
+use iced::widget::text;
+use iced::widget::text_editor;
+use iced::widget::text_input;
+use iced::widget::{column, container};
+use iced::{Alignment, Element, Fill, Font, Theme};
+use chrono::Datelike;
+use task::dates;
+use task::i18n::{self, t};
+use task::models::{AttachmentStatus, Change, Task};
+use task::repositories;
+use task::selection::Selection;
+
+struct TaskManagerApp {
+    tasks: Vec<Task>,
+    is_locked: bool,
+    passphrase_input: String,
+    error_message: Option<String>,
+    filter: String,
+    selected_task: Option<u32>,
+    description: String,
+    notes: text_editor::Content,
+    theme: Theme,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    PassphraseChanged(String),
+    UnlockPressed,
+    FilterChanged(String),
+    SelectTask(u32),
+    DescriptionChanged(String),
+    NotesEdited(text_editor::Action),
+    SaveTask,
+    CancelEdit,
+    ToggleCompleted(u32, bool),
+    DeleteTask(u32),
+    ThemeChanged(Theme),
+    Refresh,
+}
+
+impl Default for TaskManagerApp {
+    fn default() -> Self {
+        let is_locked = repositories::is_encrypted();
+
+        Self {
+            tasks: if is_locked {
+                Vec::new()
+            } else {
+                repositories::read()
+            },
+            is_locked,
+            passphrase_input: String::new(),
+            error_message: None,
+            filter: String::new(),
+            selected_task: None,
+            description: String::new(),
+            notes: text_editor::Content::new(),
+            theme: Theme::Nightfly,
+        }
+    }
+}
+
+impl TaskManagerApp {
+    fn update(&mut self, message: Message) -> iced::Task<Message> {
+        match message {
+            Message::PassphraseChanged(input) => {
+                self.passphrase_input = input;
+            }
+            Message::UnlockPressed => match repositories::verify(&self.passphrase_input) {
+                Ok(()) => {
+                    repositories::unlock(self.passphrase_input.clone());
+                    self.is_locked = false;
+                    self.error_message = None;
+                    self.tasks = repositories::read();
+                }
+                Err(e) => self.error_message = Some(e),
+            },
+            Message::FilterChanged(filter) => {
+                self.filter = filter;
+            }
+            Message::SelectTask(id) => {
+                if let Some(task) = self.tasks.iter().find(|task| task.id == id) {
+                    self.selected_task = Some(id);
+                    self.description = task.description.clone();
+                    self.notes = text_editor::Content::with_text(&task.notes);
+                }
+            }
+            Message::DescriptionChanged(description) => {
+                self.description = description;
+            }
+            Message::NotesEdited(action) => {
+                self.notes.perform(action);
+            }
+            Message::SaveTask => {
+                if self.description.trim().is_empty() {
+                    return iced::Task::none();
+                }
+
+                let interpretation = dates::interpret(&self.description, Local::now().naive_local());
+                let notes = self.notes.text().trim_end().to_string();
+
+                let change = match self.selected_task {
+                    Some(id) => {
+                        let Some(mut task) = self.current(id) else {
+                            return iced::Task::none();
+                        };
+                        task.description = interpretation.description;
+                        task.notes = notes;
+                        if interpretation.due.is_some() {
+                            task.due = interpretation.due;
+                            task.recurrence = interpretation.recurrence;
+                        }
+                        Change::Edited(task)
+                    }
+                    None => Change::Created(Task {
+                        // The CLI may have added tasks since they were loaded.
+                        id: (repositories::read().len() + 1).try_into().unwrap(),
+                        description: interpretation.description,
+                        completed: false,
+                        notes,
+                        attachments: Vec::new(),
+                        due: interpretation.due,
+                        recurrence: interpretation.recurrence,
+                        pomodoros: 0,
+                    }),
+                };
+
+                if let Err(e) = repositories::append(vec![change]) {
+                    self.error_message = Some(e);
+                    return iced::Task::none();
+                }
+                self.error_message = None;
+                self.reload();
+                self.clear_form();
+            }
+            Message::CancelEdit => {
+                self.clear_form();
+            }
+            Message::ToggleCompleted(id, completed) => {
+                let Some(mut task) = self.current(id) else {
+                    return iced::Task::none();
+                };
+
+                let changes = if completed {
+                    Change::completion(&task)
+                } else {
+                    task.completed = false;
+                    vec![Change::Edited(task)]
+                };
+
+                self.error_message = repositories::append(changes).err();
+                self.reload();
+            }
+            Message::DeleteTask(id) => {
+                if self.current(id).is_none() {
+                    return iced::Task::none();
+                }
+                self.error_message = repositories::append(vec![Change::Deleted(id)]).err();
+                self.reload();
+                self.clear_form();
+            }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+            }
+            Message::Refresh => {
+                self.reload();
+            }
+        }
+        iced::Task::none()
+    }
+
+    fn find(&self, id: u32) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    /// The stored task `id`, if it is still the one shown. Ids are positions, so a task deleted
+    /// from the CLI meanwhile would make the GUI change the next one instead.
+    fn current(&mut self, id: u32) -> Option<Task> {
+        let shown = self.find(id).cloned();
+        let stored = repositories::read().into_iter().find(|task| task.id == id);
+
+        if shown.is_some() && stored == shown {
+            return stored;
+        }
+
+        self.error_message = Some(t("not_found").to_string());
+        self.reload();
+        self.clear_form();
+        None
+    }
+
+    /// Re-reads the tasks, dropping the selection if another task has taken its id.
+    fn reload(&mut self) {
+        let selected = self.selected_task.and_then(|id| self.find(id)).map(|task| task.description.clone());
+        self.tasks = repositories::read();
+
+        if let Some(id) = self.selected_task
+            && self.find(id).map(|task| &task.description) != selected.as_ref()
+        {
+            self.clear_form();
+        }
+    }
+
+    fn clear_form(&mut self) {
+        self.selected_task = None;
+        self.description.clear();
+        self.notes = text_editor::Content::new();
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        if self.is_locked {
+            self.view_unlock()
+        } else {
+            self.view_dashboard()
+        }
+    }
+
+    fn view_unlock(&self) -> Element<'_, Message> {
+        let title = text(t("gui_title")).size(82).font(Font::MONOSPACE);
+
+        let subtitle = text(t("gui_locked")).size(22);
+
+        let passphrase_input = text_input(t("gui_passphrase"), &self.passphrase_input)
+            .secure(true)
+            .on_input(Message::PassphraseChanged)
+            .on_submit(Message::UnlockPressed)
+            .padding(15)
+            .size(18);
+
+        let unlock_button = button(text(t("gui_unlock")).font(Font::MONOSPACE))
+            .on_press(Message::UnlockPressed)
+            .padding([12, 60])
+            .style(button::primary);
+
+        let error_text = if let Some(err) = &self.error_message {
+            text(err).color([1.0, 0.3, 0.3]).size(14)
+        } else {
+            text("")
+        };
+
+        let unlock_page = container(
+            column![title, subtitle, passphrase_input, unlock_button, error_text]
+                .spacing(25)
+                .align_x(Alignment::Center),
+        )
+        .padding(40);
+
+        container(unlock_page)
+            .width(Fill)
+            .height(Fill)
+            .center(Fill)
+            .into()
+    }
+
+    fn view_dashboard(&self) -> Element<'_, Message> {
+        let filter = Selection::parse(&self.filter).ok();
+
+        let sidebar = container(
+            column![
+                text(t("gui_tasks")).size(24).font(Font::MONOSPACE),
+                text_input(t("gui_filter"), &self.filter)
+                    .on_input(Message::FilterChanged)
+                    .padding(10),
+                scrollable(
+                    column(
+                        self.tasks
+                            .iter()
+                            .filter(|task| filter.as_ref().is_none_or(|filter| filter.matches(task)))
+                            .map(|task| {
+                                let id = task.id;
+                                row![
+                                    checkbox(task.completed)
+                                        .on_toggle(move |completed| Message::ToggleCompleted(id, completed)),
+                                    button(text(format!("{}. {}", task.id, task.description)))
+                                        .on_press(Message::SelectTask(id))
+                                        .width(Fill)
+                                        .padding(10)
+                                        .style(if self.selected_task == Some(id) {
+                                            button::primary
+                                        } else {
+                                            button::secondary
+                                        }),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center)
+                                .into()
+                            })
+                            .collect::<Vec<_>>()
+                    )
+                    .spacing(8)
+                )
+                .height(Fill),
+                row![
+                    text(t("gui_theme")).size(16),
+                    pick_list(Theme::ALL, Some(&self.theme), Message::ThemeChanged),
+                    button(t("gui_refresh"))
+                        .on_press(Message::Refresh)
+                        .style(button::secondary),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(20),
+        )
+        .width(360)
+        .padding(20);
+
+        let details_area = container(
+            if let Some(task) = self.selected_task.and_then(|id| self.find(id)) {
+                let mut details = column![
+                    text(&task.description).size(40).font(Font::MONOSPACE),
+                    text(if task.completed { t("gui_completed") } else { t("gui_pending") }).size(18),
+                ]
+                .spacing(15)
+                .align_x(Alignment::Center);
+
+                if let Some(due) = task.due {
+                    let recurrence = task
+                        .recurrence
+                        .map(|recurrence| format!(", {}", recurrence))
+                        .unwrap_or_default();
+                    let date = format!("{}{}", due.format("%Y-%m-%d %H:%M"), recurrence);
+                    details = details.push(text(t("gui_due").replace("{date}", &date)).size(18));
+                }
+                if task.pomodoros > 0 {
+                    details = details.push(text(t("pomodoros").replace("{count}", &task.pomodoros.to_string())).size(18));
+                }
+                for attachment in &task.attachments {
+                    let status = AttachmentStatus::of(attachment).label();
+                    details = details.push(text(format!("{} {}", status, attachment)).size(16).font(Font::MONOSPACE));
+                }
+
+                details.push(
+                    button(t("gui_delete"))
+                        .on_press(Message::DeleteTask(task.id))
+                        .style(button::danger)
+                        .padding([10, 20]),
+                )
+            } else {
+                column![text(t("gui_select")).size(32)]
+            },
+        )
+        .width(Fill)
+        .height(Fill)
+        .center_x(Fill)
+        .center_y(Fill);
+
+        let interpretation = dates::interpret(&self.description, Local::now().naive_local());
+        let preview = match (interpretation.due, interpretation.recurrence) {
+            (Some(due), recurrence) => {
+                let recurrence = recurrence.map(|recurrence| format!(", {}", recurrence)).unwrap_or_default();
+                let date = format!("{} {}{}", i18n::weekday(due.weekday()), due.format("%Y-%m-%d %H:%M"), recurrence);
+                t("gui_due").replace("{date}", &date)
+            }
+            _ => String::new(),
+        };
+
+        let form = container(
+            column![
+                text(if self.selected_task.is_some() {
+                    t("gui_edit")
+                } else {
+                    t("gui_new")
+                })
+                .size(14),
+                text_input(t("gui_description"), &self.description)
+                    .on_input(Message::DescriptionChanged)
+                    .on_submit(Message::SaveTask),
+                text(preview).size(14),
+                text_editor(&self.notes)
+                    .placeholder(t("gui_notes"))
+                    .on_action(Message::NotesEdited)
+                    .height(120),
+                row![
+                    button(if self.selected_task.is_some() {
+                        t("gui_update")
+                    } else {
+                        t("gui_save")
+                    })
+                    .on_press(Message::SaveTask)
+                    .width(Fill),
+                    button(t("gui_cancel"))
+                        .on_press(Message::CancelEdit)
+                        .style(button::secondary),
+                ]
+                .spacing(10)
+            ]
+            .spacing(15),
+        )
+        .padding(20);
+
+        let error_text = if let Some(err) = &self.error_message {
+            text(err).color([1.0, 0.3, 0.3]).size(14)
+        } else {
+            text("")
+        };
+
+        row![sidebar, column![details_area, error_text, form]].into()
+    }
+}
+
+fn main() -> iced::Result {
+    i18n::init(None);
+
+    iced::application(
+        TaskManagerApp::default,
+        TaskManagerApp::update,
+        TaskManagerApp::view,
+    )
+    .title(t("gui_window_title"))
+    .theme(|app: &TaskManagerApp| app.theme.clone())
+    .run()
+}
@@ -1,6 +1,10 @@
-use crate::repositories;
+use std::collections::HashMap;
+use chrono::Local;
+use task::dates::{self, Interpretation};
+use task::{repositories, templates};
 use crate::views;
-use crate::models::Task;
+use task::models::{Change, Task, Template};
+use task::selection::{self, Selection};
 
 
 pub fn list_tasks() {
@@ -11,89 +15,373 @@ pub fn list_tasks() {
 
 pub fn add_task() {
 
-    let task_description = views::ask_description();
-    let mut tasks = repositories::read();
+    let interpretation = interpret_description(views::ask_description());
+    let tasks = repositories::read();
     let task_id = (tasks.len() + 1).try_into().unwrap();
 
     let new_task = Task {
         id: task_id,
-        description: task_description,
+        description: interpretation.description,
         completed: false,
+        notes: String::new(),
+        attachments: Vec::new(),
+        due: interpretation.due,
+        recurrence: interpretation.recurrence,
+        pomodoros: 0,
     };
 
-    tasks.push(new_task);
+    if let Err(e) = repositories::append(vec![Change::Created(new_task)]) {
+        views::vetoed(e);
+        return;
+    }
 
-    repositories::write(tasks);
     views::add_success();
 }
 
 
 pub fn edit_task() {
 
-    let task_to_edit = views::ask_task_to_edit();
-    let task_description = views::ask_description();
-    let mut tasks = repositories::read();
-    let task_count = tasks.len().try_into().unwrap();
+    let tasks = repositories::read();
 
-    if task_to_edit == 0 || task_to_edit > task_count {
+    let Some(task) = select_one(&views::ask_task_to_edit(), &tasks) else {
         views::not_found();
         return;
+    };
+
+    let interpretation = interpret_description(views::ask_description());
+    let mut task = task.clone();
+
+    task.description = interpretation.description;
+    if interpretation.due.is_some() {
+        task.due = interpretation.due;
+        task.recurrence = interpretation.recurrence;
     }
 
-    for task in tasks.iter_mut(){
-        if task.id == task_to_edit {
-            task.description = task_description;
-            break;
-        }
+    if let Err(e) = repositories::append(vec![Change::Edited(task)]) {
+        views::vetoed(e);
+        return;
     }
 
-    repositories::write(tasks);
     views::edit_success();
 }
 
 
-pub fn mark_task() {
+/// Resolves a task typed on the command line, by id or by part of its description.
+pub fn find_task(input: &str) -> Option<u32> {
 
-    let task_to_mark = views::ask_task_to_mark();
-    let mut tasks = repositories::read();
-    let task_count = tasks.len().try_into().unwrap();
+    let selection = match Selection::parse(input) {
+        Ok(selection) => selection,
+        Err(e) => {
+            views::invalid_selection(e);
+            return None;
+        }
+    };
+
+    let tasks = repositories::read();
+    let task = select_one(&selection, &tasks);
+
+    if task.is_none() {
+        views::not_found();
+    }
+    task.map(|task| task.id)
+}
+
+
+/// Tasks matching the selection. An ambiguous fuzzy match is narrowed down with a pick list.
+fn select<'a>(selection: &Selection, tasks: &'a [Task]) -> Vec<&'a Task> {
+
+    let Selection::Fuzzy(query) = selection else {
+        return tasks.iter().filter(|task| selection.matches(task)).collect();
+    };
+
+    let ranked = selection::rank(query, tasks);
+
+    if ranked.is_empty() {
+        return ranked;
+    }
+
+    match selection::unique_match(query, &ranked) {
+        Some(task) => vec![task],
+        None => views::pick_task(&ranked).into_iter().collect(),
+    }
+}
+
+
+fn select_one<'a>(selection: &Selection, tasks: &'a [Task]) -> Option<&'a Task> {
+
+    let selected = select(selection, tasks);
+
+    match selected.len() {
+        0 => None,
+        1 => Some(selected[0]),
+        _ => views::pick_task(&selected),
+    }
+}
+
+
+/// Reads a due date and a recurrence out of the description, keeping the raw text
+/// if the user rejects the interpretation.
+fn interpret_description(input: String) -> Interpretation {
+
+    let interpretation = dates::interpret(&input, Local::now().naive_local());
+
+    if interpretation.due.is_none() || views::confirm_interpretation(&interpretation) {
+        interpretation
+    } else {
+        Interpretation { description: input, due: None, recurrence: None }
+    }
+}
 
-    if task_to_mark == 0 || task_to_mark > task_count {
+
+pub fn mark_tasks(selection: Selection, confirmed: bool) {
+
+    let tasks = repositories::read();
+    let selected = select(&selection, &tasks);
+
+    if selected.is_empty() {
         views::not_found();
         return;
     }
 
-    for task in tasks.iter_mut(){
-        if task.id == task_to_mark {
-            task.completed = true;
-            break;
-        }
+    if !confirmed && selected.len() > 1 && !views::confirm_bulk("mark", &selected) {
+        views::cancelled();
+        return;
+    }
+
+    let count = selected.len();
+    let changes = selected.into_iter().flat_map(Change::completion).collect();
+
+    if let Err(e) = repositories::append(changes) {
+        views::vetoed(e);
+        return;
+    }
+
+    views::mark_success(count);
+}
+
+
+pub fn delete_tasks(selection: Selection, confirmed: bool) {
+
+    let tasks = repositories::read();
+    let mut selected = select(&selection, &tasks);
+
+    if selected.is_empty() {
+        views::not_found();
+        return;
+    }
+
+    if !confirmed && selected.len() > 1 && !views::confirm_bulk("delete", &selected) {
+        views::cancelled();
+        return;
+    }
+
+    let count = selected.len();
+
+    // Deleting from the highest id keeps the lower ids valid while the log is replayed.
+    selected.sort_by_key(|task| task.id);
+    let changes = selected.iter().rev().map(|task| Change::Deleted(task.id)).collect();
+
+    if let Err(e) = repositories::append(changes) {
+        views::vetoed(e);
+        return;
+    }
+
+    views::delete_success(count);
+}
+
+
+pub fn show_task(task_id: u32) {
+
+    let tasks = repositories::read();
+
+    match tasks.into_iter().find(|task| task.id == task_id) {
+        Some(task) => views::display_task(task),
+        None => views::not_found(),
+    }
+}
+
+
+pub fn edit_notes(task_id: u32) {
+
+    let mut tasks = repositories::read();
+
+    let Some(task) = tasks.iter_mut().find(|task| task.id == task_id) else {
+        views::not_found();
+        return;
+    };
+
+    task.notes = views::ask_notes(&task.notes);
+
+    if let Err(e) = repositories::append(vec![Change::Edited(task.clone())]) {
+        views::vetoed(e);
+        return;
     }
 
-    repositories::write(tasks);
-    views::mark_success();
+    views::notes_success();
 }
 
 
-pub fn delete_task() {
+pub fn attach_file(task_id: u32, reference: String) {
 
-    let task_to_delete = views::ask_task_to_delete();
     let mut tasks = repositories::read();
-    let task_count = tasks.len().try_into().unwrap();
 
-    if task_to_delete == 0 || task_to_delete > task_count {
+    let Some(task) = tasks.iter_mut().find(|task| task.id == task_id) else {
         views::not_found();
         return;
+    };
+
+    if !task.attachments.contains(&reference) {
+        task.attachments.push(reference);
+    }
+
+    if let Err(e) = repositories::append(vec![Change::Edited(task.clone())]) {
+        views::vetoed(e);
+        return;
+    }
+
+    views::attach_success();
+}
+
+
+pub fn show_history(task_id: u32) {
+
+    match repositories::history(task_id) {
+        Some((task, events)) => views::display_history(task, events),
+        None => views::not_found(),
     }
+}
+
+
+pub fn compact() {
+
+    views::compact_success(repositories::compact());
+}
+
+
+pub fn encrypt_store(passphrase: Option<String>) {
+
+    if repositories::is_encrypted() {
+        views::already_encrypted();
+        return;
+    }
+
+    repositories::encrypt(&passphrase.unwrap_or_else(views::ask_new_passphrase));
+    views::encrypt_success();
+}
+
+
+pub fn decrypt_store() {
+
+    if !repositories::is_encrypted() {
+        views::not_encrypted();
+        return;
+    }
+
+    repositories::decrypt();
+    views::decrypt_success();
+}
+
+
+/// Runs `cycles` work intervals on a task, with breaks in between, logging each
+/// pomodoro once its work interval is over.
+pub fn focus(task_id: u32, work_minutes: u64, break_minutes: u64, cycles: u32) {
 
-    tasks.retain(|task| task.id != task_to_delete);
+    let Some(task) = repositories::read().into_iter().find(|task| task.id == task_id) else {
+        views::not_found();
+        return;
+    };
+
+    views::focus_start(&task);
+
+    for cycle in 1..=cycles {
+        views::countdown(&format!("{}/{}", cycle, cycles), "focus_work", work_minutes * 60);
+        if let Err(e) = repositories::append(vec![Change::Pomodoro(task_id)]) {
+            views::vetoed(e);
+            return;
+        }
+        views::pomodoro_done(task.pomodoros + cycle);
 
-    for task in tasks.iter_mut(){
-        if task.id > task_to_delete {
-            task.id = task.id - 1;
+        if cycle < cycles {
+            views::countdown(&format!("{}/{}", cycle, cycles), "focus_break", break_minutes * 60);
         }
     }
 
-    repositories::write(tasks);
-    views::delete_success();
-}
\ No newline at end of file
+    views::focus_success();
+}
+
+
+pub fn show_stats() {
+
+    views::display_stats(repositories::read(), Local::now().naive_local());
+}
+
+
+pub fn list_templates() {
+
+    views::display_templates(templates::read());
+}
+
+
+pub fn save_template(name: String, tasks: Vec<String>) {
+
+    templates::save(Template { name: name.clone(), tasks });
+    views::template_saved(&name);
+}
+
+
+pub fn remove_template(name: String) {
+
+    if templates::remove(&name) {views::template_removed(&name)} else {views::template_not_found(&name)};
+}
+
+
+/// Creates the tasks of a template, asking for the placeholders not given as `name=value`.
+pub fn use_template(name: String, values: Vec<String>) {
+
+    let Some(template) = templates::find(&name) else {
+        views::template_not_found(&name);
+        return;
+    };
+
+    let mut given = HashMap::new();
+    for value in values {
+        let Some((key, value)) = value.split_once('=') else {
+            views::invalid_value(&value);
+            return;
+        };
+        given.insert(key.trim().to_string(), value.to_string());
+    }
+
+    for placeholder in templates::placeholders(&template) {
+        given.entry(placeholder).or_insert_with_key(|placeholder| views::ask_placeholder(placeholder));
+    }
+
+    let now = Local::now().naive_local();
+    let first_id = repositories::read().len() + 1;
+
+    let changes: Vec<Change> = templates::instantiate(&template, &given)
+        .into_iter()
+        .enumerate()
+        .map(|(index, description)| {
+            let interpretation = dates::interpret(&description, now);
+            Change::Created(Task {
+                id: (first_id + index).try_into().unwrap(),
+                description: interpretation.description,
+                completed: false,
+                notes: String::new(),
+                attachments: Vec::new(),
+                due: interpretation.due,
+                recurrence: interpretation.recurrence,
+                pomodoros: 0,
+            })
+        })
+        .collect();
+
+    let count = changes.len();
+    if let Err(e) = repositories::append(changes) {
+        views::vetoed(e);
+        return;
+    }
+
+    views::template_success(count, &name);
+}
@@ -0,0 +1,57 @@
+use aes_gcm::AeadCore;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit};
+use argon2::Argon2;
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+pub const SALT_LEN: usize = 16;
+
+pub struct CryptoManager {
+    cipher: Aes256Gcm,
+}
+
+impl CryptoManager {
+    pub fn new(passphrase: &str, salt: &[u8]) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Key derivation failure: {}", e))?;
+
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| format!("Error during initialization: {}", e))?;
+        Ok(CryptoManager { cipher })
+    }
+
+    pub fn generate_salt() -> [u8; SALT_LEN] {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    }
+
+    pub fn encrypt(&self, data: &str) -> Result<Vec<u8>, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, data.as_ref())
+            .map_err(|e| format!("Failed to encrypt: {}", e))?;
+        let mut res: Vec<u8> = nonce.to_vec();
+        res.extend_from_slice(&ciphertext);
+        Ok(res)
+    }
+
+    pub fn decrypt(&self, encrypted: &[u8]) -> Result<String, String> {
+        if encrypted.len() < 12 {
+            return Err("Corrupted data".into());
+        }
+
+        let (nonce, ciphertext) = encrypted.split_at(12);
+        let nonce = aes_gcm::Nonce::from_slice(nonce);
+
+        let plaintext = self
+            .cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| "Failed to decrypt (Wrong passphrase ?)".to_string())?;
+        String::from_utf8(plaintext).map_err(|e| format!("Invalid UTF-8 sequence: {}", e))
+    }
+}
@@ -0,0 +1,416 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use std::fmt;
+use crate::i18n::{self, t};
+use crate::models::Recurrence;
+
+
+/// Time used when a due date is given without an hour ("tomorrow", "on the 1st").
+const DEFAULT_HOUR: u32 = 9;
+
+/// Words dropped from the description when they introduce a date ("at 3pm", "on the 1st").
+const LINKING_WORDS: [&str; 5] = ["on", "at", "by", "due", "the"];
+
+
+/// What was understood from a description typed by the user.
+pub struct Interpretation {
+    pub description: String,
+    pub due: Option<NaiveDateTime>,
+    pub recurrence: Option<Recurrence>,
+}
+
+
+/// Splits sentences like "Call supplier tomorrow 3pm", "Pay rent every month on the 1st"
+/// or "Send report 2026-11-02 14:00" into a description and a due date relative to `now`.
+pub fn interpret(input: &str, now: NaiveDateTime) -> Interpretation {
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let lower: Vec<String> = words.iter().map(|word| word.trim_end_matches([',', '.', ';']).to_lowercase()).collect();
+    let mut used = vec![false; words.len()];
+
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut recurrence: Option<Recurrence> = None;
+    let today = now.date();
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = lower[i].as_str();
+        let next = lower.get(i + 1).map(String::as_str);
+        let start = i;
+
+        if word == "every" {
+            match next {
+                Some("day") => { recurrence = Some(Recurrence::Daily); i += 2; }
+                Some("week") => { recurrence = Some(Recurrence::Weekly(today.weekday())); i += 2; }
+                Some("month") => {
+                    let (day, len) = monthly_day(&lower[i + 2..]).unwrap_or((today.day(), 0));
+                    recurrence = Some(Recurrence::Monthly(day));
+                    i += 2 + len;
+                }
+                Some(name) => match parse_weekday(name, true) {
+                    Some(weekday) => { recurrence = Some(Recurrence::Weekly(weekday)); i += 2; }
+                    None => { i += 1; continue; }
+                },
+                None => { i += 1; continue; }
+            }
+        } else if word == "daily" {
+            recurrence = Some(Recurrence::Daily);
+            i += 1;
+        } else if word == "weekly" {
+            recurrence = Some(Recurrence::Weekly(today.weekday()));
+            i += 1;
+        } else if word == "monthly" {
+            recurrence = Some(Recurrence::Monthly(today.day()));
+            i += 1;
+        } else if word == "today" {
+            date = Some(today);
+            i += 1;
+        } else if word == "tonight" {
+            date = Some(today);
+            time = time.or(NaiveTime::from_hms_opt(20, 0, 0));
+            i += 1;
+        } else if word == "tomorrow" {
+            date = Some(today + Duration::days(1));
+            i += 1;
+        } else if word == "next" && next == Some("week") {
+            date = Some(today + Duration::weeks(1));
+            i += 2;
+        } else if matches!(word, "next" | "on") && let Some(weekday) = next.and_then(|name| parse_weekday(name, true)) {
+            date = Some(next_weekday(today, weekday));
+            i += 2;
+        } else if let Some(weekday) = parse_weekday(word, false) {
+            date = Some(next_weekday(today, weekday));
+            i += 1;
+        } else if word == "in"
+            && let Some((offset, len)) = parse_offset(&lower[i + 1..])
+            && let Some(target) = now.checked_add_signed(offset)
+        {
+            date = Some(target.date());
+            if offset < Duration::days(1) {
+                time = NaiveTime::from_hms_opt(target.hour(), target.minute(), 0);
+            }
+            i += 1 + len;
+        } else if let Some((parsed_date, parsed_time)) = parse_iso(word) {
+            date = Some(parsed_date);
+            time = parsed_time.or(time);
+            i += 1;
+        } else if let Some((parsed_time, len)) = parse_time(&lower[i..]) {
+            time = Some(parsed_time);
+            i += len;
+        } else {
+            i += 1;
+            continue;
+        }
+
+        used[start..i].fill(true);
+        let mut before = start;
+        while before > 0 && !used[before - 1] && LINKING_WORDS.contains(&lower[before - 1].as_str()) {
+            before -= 1;
+            used[before] = true;
+        }
+    }
+
+    let description = words.iter().zip(&used).filter(|(_, used)| !**used).map(|(word, _)| *word).collect::<Vec<_>>().join(" ");
+
+    if description.is_empty() || (date.is_none() && time.is_none() && recurrence.is_none()) {
+        return Interpretation { description: input.trim().to_string(), due: None, recurrence: None };
+    }
+
+    let default_time = NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0).unwrap();
+
+    let due = match (date, time, recurrence) {
+        (Some(date), time, _) => date.and_time(time.unwrap_or(default_time)),
+        (None, time, Some(recurrence)) => first_occurrence(recurrence, now, time.unwrap_or(default_time)),
+        (None, Some(time), None) => {
+            if time > now.time() {today.and_time(time)} else {(today + Duration::days(1)).and_time(time)}
+        }
+        (None, None, None) => unreachable!(),
+    };
+
+    Interpretation { description, due: Some(due), recurrence }
+}
+
+
+impl Recurrence {
+    /// Due date following `due` once a recurring task is completed.
+    pub fn next_after(&self, due: NaiveDateTime) -> NaiveDateTime {
+
+        match self {
+            Recurrence::Daily => due + Duration::days(1),
+            Recurrence::Weekly(_) => due + Duration::weeks(1),
+            Recurrence::Monthly(day) => {
+                let next_month = due.date().with_day(1).unwrap() + Months::new(1);
+                day_in_month(next_month, *day).and_time(due.time())
+            }
+        }
+    }
+}
+
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "{}", t("every_day")),
+            Recurrence::Weekly(weekday) => write!(f, "{}", t("every_weekday").replace("{day}", i18n::weekday(*weekday))),
+            Recurrence::Monthly(day) => write!(f, "{}", t("every_month").replace("{day}", &i18n::ordinal(*day))),
+        }
+    }
+}
+
+
+fn first_occurrence(recurrence: Recurrence, now: NaiveDateTime, time: NaiveTime) -> NaiveDateTime {
+
+    let today = now.date();
+    let candidate = match recurrence {
+        Recurrence::Daily => today,
+        Recurrence::Weekly(weekday) => today + Duration::days(days_until(today.weekday(), weekday)),
+        Recurrence::Monthly(day) => day_in_month(today, day),
+    }.and_time(time);
+
+    if candidate >= now {candidate} else {recurrence.next_after(candidate)}
+}
+
+
+/// Day `day` of the month containing `date`, clamped to the last day of shorter months.
+fn day_in_month(date: NaiveDate, day: u32) -> NaiveDate {
+
+    let first = date.with_day(1).unwrap();
+    let last = (first + Months::new(1) - Duration::days(1)).day();
+    first.with_day(day.clamp(1, last)).unwrap()
+}
+
+
+fn days_until(from: Weekday, to: Weekday) -> i64 {
+
+    (7 + to.num_days_from_monday() as i64 - from.num_days_from_monday() as i64) % 7
+}
+
+
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+
+    match days_until(today.weekday(), weekday) {
+        0 => today + Duration::weeks(1),
+        days => today + Duration::days(days),
+    }
+}
+
+
+/// Reads a day name. Abbreviations are only `abbreviated` after "on", "next" or "every",
+/// where they cannot be mistaken for words of the description ("Buy sun cream").
+fn parse_weekday(word: &str, abbreviated: bool) -> Option<Weekday> {
+
+    match word {
+        "monday" => Some(Weekday::Mon),
+        "tuesday" => Some(Weekday::Tue),
+        "wednesday" => Some(Weekday::Wed),
+        "thursday" => Some(Weekday::Thu),
+        "friday" => Some(Weekday::Fri),
+        "saturday" => Some(Weekday::Sat),
+        "sunday" => Some(Weekday::Sun),
+        "mon" if abbreviated => Some(Weekday::Mon),
+        "tue" if abbreviated => Some(Weekday::Tue),
+        "wed" if abbreviated => Some(Weekday::Wed),
+        "thu" if abbreviated => Some(Weekday::Thu),
+        "fri" if abbreviated => Some(Weekday::Fri),
+        "sat" if abbreviated => Some(Weekday::Sat),
+        "sun" if abbreviated => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+
+/// Reads "on the 1st", "the 15th" or "1st" after "every month", returning the day and the words used.
+fn monthly_day(words: &[String]) -> Option<(u32, usize)> {
+
+    let skipped = words.iter().take_while(|word| matches!(word.as_str(), "on" | "the")).count();
+    let word = words.get(skipped)?;
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+
+    if !matches!(suffix, "st" | "nd" | "rd" | "th" | "") {
+        return None;
+    }
+
+    match digits.parse::<u32>() {
+        Ok(day @ 1..=31) => Some((day, skipped + 1)),
+        _ => None,
+    }
+}
+
+
+/// Reads "2 days", "3 weeks", "1 hour" or "30 minutes" after "in". Negative amounts, and amounts
+/// too large for a duration, are not offsets.
+fn parse_offset(words: &[String]) -> Option<(Duration, usize)> {
+
+    let amount: i64 = words.first()?.parse().ok().filter(|amount| *amount >= 0)?;
+    let offset = match words.get(1)?.as_str() {
+        "minute" | "minutes" | "min" | "mins" => Duration::try_minutes(amount),
+        "hour" | "hours" => Duration::try_hours(amount),
+        "day" | "days" => Duration::try_days(amount),
+        "week" | "weeks" => Duration::try_weeks(amount),
+        _ => return None,
+    };
+    offset.map(|offset| (offset, 2))
+}
+
+
+/// Reads ISO dates such as "2026-11-02", "2026-11-02t14:00" or "2026-11-02t14:00:00".
+fn parse_iso(word: &str) -> Option<(NaiveDate, Option<NaiveTime>)> {
+
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some((date, None));
+    }
+
+    ["%Y-%m-%dt%H:%M", "%Y-%m-%dt%H:%M:%S"].iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(word, format).ok())
+        .map(|due| (due.date(), Some(due.time())))
+}
+
+
+/// Reads "15:00", "3pm", "3:30pm", "3 pm", "noon" or "midnight", returning the time and the words used.
+fn parse_time(words: &[String]) -> Option<(NaiveTime, usize)> {
+
+    let word = words.first()?.as_str();
+
+    match word {
+        "noon" => return Some((NaiveTime::from_hms_opt(12, 0, 0)?, 1)),
+        "midnight" => return Some((NaiveTime::from_hms_opt(0, 0, 0)?, 1)),
+        _ => {}
+    }
+
+    let (clock, meridiem, len) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false), 1)
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true), 1)
+    } else {
+        match words.get(1).map(String::as_str) {
+            Some("am") => (word, Some(false), 2),
+            Some("pm") => (word, Some(true), 2),
+            _ => (word, None, 1),
+        }
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| (time, len))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday morning.
+    fn now() -> NaiveDateTime {
+        at("2026-10-14 10:00")
+    }
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn assert_unchanged(input: &str) {
+        let interpretation = interpret(input, now());
+
+        assert_eq!(interpretation.description, input);
+        assert_eq!(interpretation.due, None);
+        assert_eq!(interpretation.recurrence, None);
+    }
+
+    #[test]
+    fn reads_a_relative_day_and_an_hour() {
+        let interpretation = interpret("Call supplier tomorrow 3pm", now());
+
+        assert_eq!(interpretation.description, "Call supplier");
+        assert_eq!(interpretation.due, Some(at("2026-10-15 15:00")));
+        assert_eq!(interpretation.recurrence, None);
+    }
+
+    #[test]
+    fn reads_a_monthly_recurrence() {
+        let interpretation = interpret("Pay rent every month on the 1st", now());
+
+        assert_eq!(interpretation.description, "Pay rent");
+        assert_eq!(interpretation.due, Some(at("2026-11-01 09:00")));
+        assert_eq!(interpretation.recurrence, Some(Recurrence::Monthly(1)));
+    }
+
+    #[test]
+    fn reads_iso_dates_with_and_without_a_time() {
+        let interpretation = interpret("Send report 2026-11-02", now());
+        assert_eq!(interpretation.description, "Send report");
+        assert_eq!(interpretation.due, Some(at("2026-11-02 09:00")));
+
+        let interpretation = interpret("Send report 2026-11-02T14:00", now());
+        assert_eq!(interpretation.description, "Send report");
+        assert_eq!(interpretation.due, Some(at("2026-11-02 14:00")));
+    }
+
+    #[test]
+    fn reads_offsets() {
+        let interpretation = interpret("Water plants in 3 days", now());
+        assert_eq!(interpretation.description, "Water plants");
+        assert_eq!(interpretation.due, Some(at("2026-10-17 09:00")));
+
+        let interpretation = interpret("Take the cake out in 30 minutes", now());
+        assert_eq!(interpretation.description, "Take the cake out");
+        assert_eq!(interpretation.due, Some(at("2026-10-14 10:30")));
+    }
+
+    #[test]
+    fn ignores_offsets_out_of_range() {
+        assert_unchanged("Call in 99999999999 days");
+        assert_unchanged("Call in 999999999 weeks");
+        assert_unchanged("Call in 9223372036854775807 minutes");
+        assert_unchanged("Call in -3 days");
+    }
+
+    #[test]
+    fn reads_abbreviated_days_only_after_a_keyword() {
+        assert_unchanged("Buy sun cream");
+
+        let interpretation = interpret("Call mom on sun", now());
+        assert_eq!(interpretation.description, "Call mom");
+        assert_eq!(interpretation.due, Some(at("2026-10-18 09:00")));
+
+        let interpretation = interpret("Team meeting every mon 10am", now());
+        assert_eq!(interpretation.description, "Team meeting");
+        assert_eq!(interpretation.due, Some(at("2026-10-19 10:00")));
+        assert_eq!(interpretation.recurrence, Some(Recurrence::Weekly(Weekday::Mon)));
+
+        let interpretation = interpret("Review next fri", now());
+        assert_eq!(interpretation.due, Some(at("2026-10-16 09:00")));
+    }
+
+    #[test]
+    fn reads_full_day_names_anywhere() {
+        let interpretation = interpret("Call supplier friday at noon", now());
+
+        assert_eq!(interpretation.description, "Call supplier");
+        assert_eq!(interpretation.due, Some(at("2026-10-16 12:00")));
+    }
+
+    #[test]
+    fn keeps_descriptions_without_dates() {
+        assert_unchanged("Buy groceries");
+        assert_unchanged("tomorrow");
+    }
+
+    #[test]
+    fn schedules_the_next_occurrence() {
+        assert_eq!(Recurrence::Daily.next_after(at("2026-10-14 09:00")), at("2026-10-15 09:00"));
+        assert_eq!(Recurrence::Weekly(Weekday::Wed).next_after(at("2026-10-14 09:00")), at("2026-10-21 09:00"));
+        assert_eq!(Recurrence::Monthly(31).next_after(at("2026-01-31 09:00")), at("2026-02-28 09:00"));
+    }
+}
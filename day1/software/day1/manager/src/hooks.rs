@@ -0,0 +1,98 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::i18n::t;
+use crate::models::Task;
+
+
+/// Executables named `pre-<event>*` or `post-<event>*` in this directory are run on task changes,
+/// with the task as JSON on stdin and the event name in `TASK_EVENT`.
+const HOOKS_DIR: &str = "data/hooks";
+
+
+/// Runs the `pre-<event>` hooks. The first one exiting with a non-zero status vetoes the change.
+pub fn before(event: &str, task: &Task) -> Result<(), String> {
+
+    for hook in find("pre", event) {
+        let status = run(&hook, event, task)?;
+        if !status.success() {
+            return Err(t("hook_vetoed").replace("{hook}", &hook.display().to_string()).replace("{status}", &status.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Runs the `post-<event>` hooks once the change is saved. Their failures are reported but not fatal.
+pub fn after(event: &str, task: &Task) {
+
+    for hook in find("post", event) {
+        match run(&hook, event, task) {
+            Ok(status) if status.success() => {}
+            Ok(status) => eprintln!("\n{}", t("hook_failed").replace("{hook}", &hook.display().to_string()).replace("{status}", &status.to_string())),
+            Err(e) => eprintln!("\n{}", e),
+        }
+    }
+}
+
+
+fn find(stage: &str, event: &str) -> Vec<PathBuf> {
+
+    let Ok(entries) = fs::read_dir(HOOKS_DIR) else {
+        return Vec::new();
+    };
+
+    let prefix = format!("{}-{}", stage, event);
+    let mut hooks: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix)))
+        .filter(|path| is_executable(path))
+        .collect();
+
+    hooks.sort();
+    hooks
+}
+
+
+fn run(hook: &Path, event: &str, task: &Task) -> Result<std::process::ExitStatus, String> {
+
+    let payload = serde_json::to_string(task).map_err(|e| format!("Failed to serialize: {}", e))?;
+
+    let mut child = Command::new(hook)
+        .env("TASK_EVENT", event)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| run_failed(hook, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook is free to exit without reading its input.
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+
+    child.wait().map_err(|e| run_failed(hook, e))
+}
+
+
+fn run_failed(hook: &Path, error: std::io::Error) -> String {
+
+    t("hook_run_failed").replace("{hook}", &hook.display().to_string()).replace("{error}", &error.to_string())
+}
+
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+
+    path.is_file()
+}
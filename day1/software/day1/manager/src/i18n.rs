@@ -0,0 +1,411 @@
+use std::env;
+use std::sync::OnceLock;
+use chrono::Weekday;
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Locale {
+    En,
+    Fr,
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+
+const EN: &[(&str, &str)] = &[
+    ("welcome", "Welcome to your Task Manager!"),
+    ("menu", "What do you want to do?\n1 - List all tasks\n2 - Add a task\n3 - Edit a task\n4 - Mark tasks\n5 - Delete tasks\n6 - Show a task\n7 - Edit the notes of a task\n8 - Attach a file to a task\n9 - Show the history of a task\n10 - Focus on a task\n11 - Show statistics\n12 - Leave"),
+    ("menu_invalid", "Type a number between 1 and 12."),
+    ("goodbye", "See you!"),
+    ("list_header", "Here are your tasks:"),
+    ("ask_description", "Please enter a description:"),
+    ("label_description", "Description: "),
+    ("label_due", "Due:         "),
+    ("label_repeats", "Repeats:     "),
+    ("confirm_interpretation", "Save it like this? (y/n)"),
+    ("due", "due"),
+    ("ask_edit", "Which one do you want to edit? (id or part of its description)"),
+    ("retry_edit", "Type the id or part of the description of the task you want to edit."),
+    ("pick_header", "Several tasks match:"),
+    ("pick_prompt", "Type the number of the task, or leave empty to cancel."),
+    ("ask_mark", "Which ones do you want to mark? (e.g. 1-5,8,12, all, done, todo, text:<fragment> or part of a description)"),
+    ("retry_mark", "Type the ids or the filter of the tasks you want to mark."),
+    ("ask_delete", "Which ones do you want to delete? (e.g. 1-5,8,12, all, done, todo, text:<fragment> or part of a description)"),
+    ("retry_delete", "Type the ids or the filter of the tasks you want to delete."),
+    ("ask_show", "Which one do you want to show?"),
+    ("retry_show", "Type the id of the task you want to show."),
+    ("ask_annotate", "Which one do you want to annotate?"),
+    ("retry_annotate", "Type the id of the task you want to annotate."),
+    ("ask_attach", "Which one do you want to attach a file to?"),
+    ("retry_attach", "Type the id of the task you want to attach a file to."),
+    ("ask_trace", "Which one do you want to see the history of?"),
+    ("retry_trace", "Type the id of the task you want to see the history of."),
+    ("ask_attachment", "Please enter a file path or an URL:"),
+    ("editor_failed", "Could not run the editor \"{editor}\", notes left unchanged."),
+    ("no_notes", "No notes."),
+    ("notes", "Notes:"),
+    ("no_attachments", "No attachments."),
+    ("attachments", "Attachments:"),
+    ("attachment_link", "[link]   "),
+    ("attachment_ok", "[ok]     "),
+    ("attachment_missing", "[missing]"),
+    ("bulk_mark", "You are about to mark {count} tasks:"),
+    ("bulk_delete", "You are about to delete {count} tasks:"),
+    ("confirm_bulk", "Continue? (y/n)"),
+    ("history_header", "History of task {id} -- {description}:"),
+    ("history_empty", "No changes recorded since the last compaction."),
+    ("history_created", "created  "),
+    ("history_edited", "edited   "),
+    ("history_completed", "completed"),
+    ("history_deleted", "deleted  "),
+    ("history_pomodoro", "pomodoro "),
+    ("ask_focus", "Which one do you want to focus on?"),
+    ("retry_focus", "Type the id of the task you want to focus on."),
+    ("focus_start", "Focusing on task {id} -- {description}. Press Ctrl+C to stop."),
+    ("focus_work", "Work "),
+    ("focus_break", "Break"),
+    ("pomodoro_done", "Pomodoro done, {count} on this task !"),
+    ("focus_success", "Focus session over !"),
+    ("pomodoros", "{count} pomodoros"),
+    ("stats_header", "Statistics:"),
+    ("stats_tasks", "Tasks:        "),
+    ("stats_completed", "Completed:    "),
+    ("stats_pending", "Pending:      "),
+    ("stats_overdue", "Overdue:      "),
+    ("stats_pomodoros", "Pomodoros:    "),
+    ("stats_most_focused", "Most focused: "),
+    ("ask_passphrase", "Enter the passphrase of the task store : "),
+    ("ask_new_passphrase", "Enter a new passphrase : "),
+    ("confirm_passphrase", "Confirm the passphrase : "),
+    ("empty_passphrase", "The passphrase cannot be empty."),
+    ("passphrase_mismatch", "The passphrases do not match."),
+    ("add_success", "Task added !"),
+    ("edit_success", "Task edited !"),
+    ("mark_success", "Task marked !"),
+    ("mark_success_many", "{count} tasks marked !"),
+    ("delete_success", "Task deleted !"),
+    ("delete_success_many", "{count} tasks deleted !"),
+    ("cancelled", "Nothing changed."),
+    ("notes_success", "Notes saved !"),
+    ("attach_success", "File attached !"),
+    ("compact_success", "Log compacted, {count} events folded into the snapshot !"),
+    ("encrypt_success", "Task store encrypted !"),
+    ("decrypt_success", "Task store decrypted !"),
+    ("already_encrypted", "The task store is already encrypted."),
+    ("not_encrypted", "The task store is not encrypted."),
+    ("not_found", "Task not found !"),
+    ("vetoed", "Change refused:"),
+    ("hook_vetoed", "Vetoed by hook {hook} ({status})"),
+    ("hook_failed", "Hook {hook} failed ({status})"),
+    ("hook_run_failed", "Failed to run hook {hook}: {error}"),
+    ("templates_header", "Here are your templates:"),
+    ("no_templates", "No template yet."),
+    ("template_saved", "Template \"{name}\" saved !"),
+    ("template_removed", "Template \"{name}\" removed !"),
+    ("template_not_found", "Template \"{name}\" not found !"),
+    ("template_success", "{count} tasks created from \"{name}\" !"),
+    ("ask_placeholder", "Value for {name}:"),
+    ("invalid_value", "Expected name=value, got \"{value}\""),
+    ("empty_selection", "Empty selection"),
+    ("invalid_range", "Invalid range \"{range}\""),
+    ("invalid_id", "Invalid task id \"{id}\""),
+    ("gui_title", "TASK MANAGER"),
+    ("gui_window_title", "Task Manager"),
+    ("gui_locked", "This task store is encrypted"),
+    ("gui_passphrase", "Enter the passphrase"),
+    ("gui_unlock", "UNLOCK"),
+    ("gui_tasks", "TASKS"),
+    ("gui_filter", "Filter (1-5, done, todo, text:...)"),
+    ("gui_theme", "Theme:"),
+    ("gui_refresh", "Refresh"),
+    ("gui_select", "Select a task to view details"),
+    ("gui_completed", "Completed"),
+    ("gui_pending", "Pending"),
+    ("gui_due", "Due {date}"),
+    ("gui_delete", "Delete Task"),
+    ("gui_new", "NEW TASK"),
+    ("gui_edit", "EDIT TASK"),
+    ("gui_description", "Description (e.g. Call supplier tomorrow 3pm)"),
+    ("gui_notes", "Notes"),
+    ("gui_save", "Save"),
+    ("gui_update", "Update"),
+    ("gui_cancel", "Cancel"),
+    ("every_day", "every day"),
+    ("every_weekday", "every {day}"),
+    ("every_month", "every month on the {day}"),
+    ("monday", "Monday"),
+    ("tuesday", "Tuesday"),
+    ("wednesday", "Wednesday"),
+    ("thursday", "Thursday"),
+    ("friday", "Friday"),
+    ("saturday", "Saturday"),
+    ("sunday", "Sunday"),
+];
+
+
+const FR: &[(&str, &str)] = &[
+    ("welcome", "Bienvenue dans votre gestionnaire de tâches !"),
+    ("menu", "Que voulez-vous faire ?\n1 - Lister les tâches\n2 - Ajouter une tâche\n3 - Modifier une tâche\n4 - Marquer des tâches\n5 - Supprimer des tâches\n6 - Afficher une tâche\n7 - Modifier les notes d'une tâche\n8 - Joindre un fichier à une tâche\n9 - Afficher l'historique d'une tâche\n10 - Se concentrer sur une tâche\n11 - Afficher les statistiques\n12 - Quitter"),
+    ("menu_invalid", "Tapez un nombre entre 1 et 12."),
+    ("goodbye", "À bientôt !"),
+    ("list_header", "Voici vos tâches :"),
+    ("ask_description", "Veuillez saisir une description :"),
+    ("label_description", "Description : "),
+    ("label_due", "Échéance :   "),
+    ("label_repeats", "Répétition : "),
+    ("confirm_interpretation", "Enregistrer ainsi ? (o/n)"),
+    ("due", "échéance"),
+    ("ask_edit", "Laquelle voulez-vous modifier ? (id ou partie de sa description)"),
+    ("retry_edit", "Tapez l'id ou une partie de la description de la tâche à modifier."),
+    ("pick_header", "Plusieurs tâches correspondent :"),
+    ("pick_prompt", "Tapez le numéro de la tâche, ou laissez vide pour annuler."),
+    ("ask_mark", "Lesquelles voulez-vous marquer ? (ex. 1-5,8,12, all, done, todo, text:<fragment> ou partie d'une description)"),
+    ("retry_mark", "Tapez les ids ou le filtre des tâches à marquer."),
+    ("ask_delete", "Lesquelles voulez-vous supprimer ? (ex. 1-5,8,12, all, done, todo, text:<fragment> ou partie d'une description)"),
+    ("retry_delete", "Tapez les ids ou le filtre des tâches à supprimer."),
+    ("ask_show", "Laquelle voulez-vous afficher ?"),
+    ("retry_show", "Tapez l'id de la tâche à afficher."),
+    ("ask_annotate", "Laquelle voulez-vous annoter ?"),
+    ("retry_annotate", "Tapez l'id de la tâche à annoter."),
+    ("ask_attach", "À laquelle voulez-vous joindre un fichier ?"),
+    ("retry_attach", "Tapez l'id de la tâche à laquelle joindre un fichier."),
+    ("ask_trace", "De laquelle voulez-vous voir l'historique ?"),
+    ("retry_trace", "Tapez l'id de la tâche dont vous voulez voir l'historique."),
+    ("ask_attachment", "Veuillez saisir un chemin de fichier ou une URL :"),
+    ("editor_failed", "Impossible de lancer l'éditeur \"{editor}\", notes inchangées."),
+    ("no_notes", "Aucune note."),
+    ("notes", "Notes :"),
+    ("no_attachments", "Aucune pièce jointe."),
+    ("attachments", "Pièces jointes :"),
+    ("attachment_link", "[lien]    "),
+    ("attachment_ok", "[ok]      "),
+    ("attachment_missing", "[manquant]"),
+    ("bulk_mark", "Vous allez marquer {count} tâches :"),
+    ("bulk_delete", "Vous allez supprimer {count} tâches :"),
+    ("confirm_bulk", "Continuer ? (o/n)"),
+    ("history_header", "Historique de la tâche {id} -- {description} :"),
+    ("history_empty", "Aucun changement enregistré depuis le dernier compactage."),
+    ("history_created", "créée     "),
+    ("history_edited", "modifiée  "),
+    ("history_completed", "terminée  "),
+    ("history_deleted", "supprimée "),
+    ("history_pomodoro", "pomodoro  "),
+    ("ask_focus", "Sur laquelle voulez-vous vous concentrer ?"),
+    ("retry_focus", "Tapez l'id de la tâche sur laquelle vous concentrer."),
+    ("focus_start", "Concentration sur la tâche {id} -- {description}. Ctrl+C pour arrêter."),
+    ("focus_work", "Travail"),
+    ("focus_break", "Pause  "),
+    ("pomodoro_done", "Pomodoro terminé, {count} sur cette tâche !"),
+    ("focus_success", "Session de concentration terminée !"),
+    ("pomodoros", "{count} pomodoros"),
+    ("stats_header", "Statistiques :"),
+    ("stats_tasks", "Tâches :          "),
+    ("stats_completed", "Terminées :       "),
+    ("stats_pending", "En cours :        "),
+    ("stats_overdue", "En retard :       "),
+    ("stats_pomodoros", "Pomodoros :       "),
+    ("stats_most_focused", "La plus travaillée : "),
+    ("ask_passphrase", "Saisissez la phrase secrète des tâches : "),
+    ("ask_new_passphrase", "Saisissez une nouvelle phrase secrète : "),
+    ("confirm_passphrase", "Confirmez la phrase secrète : "),
+    ("empty_passphrase", "La phrase secrète ne peut pas être vide."),
+    ("passphrase_mismatch", "Les phrases secrètes ne correspondent pas."),
+    ("add_success", "Tâche ajoutée !"),
+    ("edit_success", "Tâche modifiée !"),
+    ("mark_success", "Tâche marquée !"),
+    ("mark_success_many", "{count} tâches marquées !"),
+    ("delete_success", "Tâche supprimée !"),
+    ("delete_success_many", "{count} tâches supprimées !"),
+    ("cancelled", "Rien n'a changé."),
+    ("notes_success", "Notes enregistrées !"),
+    ("attach_success", "Fichier joint !"),
+    ("compact_success", "Journal compacté, {count} événements intégrés à l'instantané !"),
+    ("encrypt_success", "Tâches chiffrées !"),
+    ("decrypt_success", "Tâches déchiffrées !"),
+    ("already_encrypted", "Les tâches sont déjà chiffrées."),
+    ("not_encrypted", "Les tâches ne sont pas chiffrées."),
+    ("not_found", "Tâche introuvable !"),
+    ("vetoed", "Changement refusé :"),
+    ("hook_vetoed", "Refusé par le hook {hook} ({status})"),
+    ("hook_failed", "Échec du hook {hook} ({status})"),
+    ("hook_run_failed", "Impossible de lancer le hook {hook} : {error}"),
+    ("templates_header", "Voici vos modèles :"),
+    ("no_templates", "Aucun modèle pour l'instant."),
+    ("template_saved", "Modèle \"{name}\" enregistré !"),
+    ("template_removed", "Modèle \"{name}\" supprimé !"),
+    ("template_not_found", "Modèle \"{name}\" introuvable !"),
+    ("template_success", "{count} tâches créées depuis \"{name}\" !"),
+    ("ask_placeholder", "Valeur pour {name} :"),
+    ("invalid_value", "Attendu nom=valeur, reçu \"{value}\""),
+    ("empty_selection", "Sélection vide"),
+    ("invalid_range", "Intervalle invalide \"{range}\""),
+    ("invalid_id", "Id de tâche invalide \"{id}\""),
+    ("gui_title", "GESTIONNAIRE DE TÂCHES"),
+    ("gui_window_title", "Gestionnaire de tâches"),
+    ("gui_locked", "Ces tâches sont chiffrées"),
+    ("gui_passphrase", "Saisissez la phrase secrète"),
+    ("gui_unlock", "DÉVERROUILLER"),
+    ("gui_tasks", "TÂCHES"),
+    ("gui_filter", "Filtre (1-5, done, todo, text:...)"),
+    ("gui_theme", "Thème :"),
+    ("gui_refresh", "Actualiser"),
+    ("gui_select", "Sélectionnez une tâche pour voir ses détails"),
+    ("gui_completed", "Terminée"),
+    ("gui_pending", "En cours"),
+    ("gui_due", "Échéance {date}"),
+    ("gui_delete", "Supprimer la tâche"),
+    ("gui_new", "NOUVELLE TÂCHE"),
+    ("gui_edit", "MODIFIER LA TÂCHE"),
+    ("gui_description", "Description (ex. Appeler le fournisseur tomorrow 3pm)"),
+    ("gui_notes", "Notes"),
+    ("gui_save", "Enregistrer"),
+    ("gui_update", "Mettre à jour"),
+    ("gui_cancel", "Annuler"),
+    ("every_day", "tous les jours"),
+    ("every_weekday", "chaque {day}"),
+    ("every_month", "chaque mois le {day}"),
+    ("monday", "lundi"),
+    ("tuesday", "mardi"),
+    ("wednesday", "mercredi"),
+    ("thursday", "jeudi"),
+    ("friday", "vendredi"),
+    ("saturday", "samedi"),
+    ("sunday", "dimanche"),
+];
+
+
+/// Picks the locale from the `--lang` flag, then `LC_ALL`, `LC_MESSAGES` and `LANG`, defaulting to English.
+pub fn init(flag: Option<String>) {
+
+    let requested = flag.or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+    });
+
+    let locale = match requested {
+        Some(value) if value.to_lowercase().starts_with("fr") => Locale::Fr,
+        _ => Locale::En,
+    };
+
+    let _ = LOCALE.set(locale);
+}
+
+
+pub fn locale() -> Locale {
+
+    *LOCALE.get().unwrap_or(&Locale::En)
+}
+
+
+/// Message for `key` in the current locale, falling back to English and then to the key itself.
+pub fn t(key: &'static str) -> &'static str {
+
+    lookup(catalog(locale()), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+
+pub fn weekday(day: Weekday) -> &'static str {
+
+    match day {
+        Weekday::Mon => t("monday"),
+        Weekday::Tue => t("tuesday"),
+        Weekday::Wed => t("wednesday"),
+        Weekday::Thu => t("thursday"),
+        Weekday::Fri => t("friday"),
+        Weekday::Sat => t("saturday"),
+        Weekday::Sun => t("sunday"),
+    }
+}
+
+
+pub fn ordinal(day: u32) -> String {
+
+    match locale() {
+        Locale::Fr if day == 1 => "1er".to_string(),
+        Locale::Fr => day.to_string(),
+        Locale::En => {
+            let suffix = match (day % 10, day % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            format!("{}{}", day, suffix)
+        }
+    }
+}
+
+
+/// Answers accepted as a yes, in every supported language.
+pub fn is_yes(answer: &str) -> bool {
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes" | "o" | "oui")
+}
+
+
+/// Answers accepted as a no, in every supported language.
+pub fn is_no(answer: &str) -> bool {
+
+    matches!(answer.trim().to_lowercase().as_str(), "n" | "no" | "non")
+}
+
+
+fn catalog(locale: Locale) -> &'static [(&'static str, &'static str)] {
+
+    match locale {
+        Locale::En => EN,
+        Locale::Fr => FR,
+    }
+}
+
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+
+    catalog.iter().find(|(name, _)| *name == key).map(|(_, message)| *message)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALES: [Locale; 2] = [Locale::En, Locale::Fr];
+
+    #[test]
+    fn every_key_exists_in_every_catalog() {
+        for locale in LOCALES {
+            for other in LOCALES {
+                for (key, _) in catalog(locale) {
+                    assert!(lookup(catalog(other), key).is_some(), "{:?} is missing \"{}\" from {:?}", other, key, locale);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn catalogs_have_no_duplicate_keys() {
+        for locale in LOCALES {
+            let keys = catalog(locale);
+            for (index, (key, _)) in keys.iter().enumerate() {
+                assert!(!keys[index + 1..].iter().any(|(other, _)| other == key), "{:?} defines \"{}\" twice", locale, key);
+            }
+        }
+    }
+
+    #[test]
+    fn placeholders_match_across_catalogs() {
+        let placeholders = |message: &str| {
+            let mut names: Vec<String> = message.split('{').skip(1).filter_map(|part| part.split_once('}')).map(|(name, _)| name.to_string()).collect();
+            names.sort();
+            names
+        };
+
+        for (key, message) in EN {
+            assert_eq!(placeholders(message), placeholders(lookup(FR, key).unwrap()), "placeholders differ for \"{}\"", key);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod crypto;
+pub mod dates;
+pub mod hooks;
+pub mod i18n;
+pub mod models;
+pub mod repositories;
+pub mod selection;
+pub mod templates;
@@ -1,9 +1,131 @@
 mod router;
 mod controllers;
 mod views;
-mod repositories;
-mod models;
+use clap::{Parser, Subcommand};
+use std::env;
+use task::{i18n, repositories};
+use task::selection::Selection;
+
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Language of the messages (en or fr), read from the locale environment by default
+    #[arg(long, global = true)]
+    lang: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Show a task with its notes and attachments (by id or part of its description)
+    Show {task: String},
+
+    /// Edit the notes of a task in $EDITOR
+    Notes {task: String},
+
+    /// Attach a file path or an URL to a task
+    Attach {task: String, reference: String},
+
+    /// Mark tasks as completed (e.g. 1-5,8,12, all, done, todo, text:<fragment> or part of a description)
+    Mark {selection: String, #[arg(short, long)] yes: bool},
+
+    /// Delete tasks (e.g. 1-5,8,12, all, done, todo, text:<fragment> or part of a description)
+    Delete {selection: String, #[arg(short, long)] yes: bool},
+
+    /// Show the timeline of a task
+    History {task: String},
+
+    /// Run pomodoro work and break intervals on a task
+    Focus {
+        task: String,
+        /// Minutes of work per pomodoro
+        #[arg(long, default_value_t = 25)]
+        work: u64,
+        /// Minutes of break between pomodoros
+        #[arg(long = "break", default_value_t = 5)]
+        rest: u64,
+        /// Number of pomodoros to run
+        #[arg(long, default_value_t = 4)]
+        cycles: u32,
+    },
+
+    /// Show statistics about the tasks and pomodoros
+    Stats,
+
+    /// Manage the task templates and create tasks from them
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommands,
+    },
+
+    /// Fold the event log into a new snapshot
+    Compact,
+
+    /// Encrypt the task store with a passphrase (read from $TASK_PASSPHRASE if set)
+    Encrypt,
+
+    /// Decrypt the task store back to plaintext
+    Decrypt,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// List the templates
+    List,
+
+    /// Save a template made of one task per description, with placeholders written like {version}
+    Save {name: String, #[arg(required = true)] tasks: Vec<String>},
+
+    /// Remove a template
+    Remove {name: String},
+
+    /// Create the tasks of a template, with values given as name=value (asked for when missing)
+    Use {name: String, values: Vec<String>},
+}
 
 fn main() {
-    router::router();
+    let cli = Cli::parse();
+    i18n::init(cli.lang);
+
+    if repositories::is_encrypted() {
+        repositories::unlock(env::var("TASK_PASSPHRASE").unwrap_or_else(|_| views::ask_passphrase()));
+    }
+
+    match cli.command {
+        Some(Commands::Show { task }) => controllers::find_task(&task).into_iter().for_each(controllers::show_task),
+        Some(Commands::Notes { task }) => controllers::find_task(&task).into_iter().for_each(controllers::edit_notes),
+        Some(Commands::Attach { task, reference }) => {
+            if let Some(id) = controllers::find_task(&task) {
+                controllers::attach_file(id, reference);
+            }
+        }
+        Some(Commands::Mark { selection, yes }) => match Selection::parse(&selection) {
+            Ok(selection) => controllers::mark_tasks(selection, yes),
+            Err(e) => views::invalid_selection(e),
+        },
+        Some(Commands::Delete { selection, yes }) => match Selection::parse(&selection) {
+            Ok(selection) => controllers::delete_tasks(selection, yes),
+            Err(e) => views::invalid_selection(e),
+        },
+        Some(Commands::History { task }) => controllers::find_task(&task).into_iter().for_each(controllers::show_history),
+        Some(Commands::Focus { task, work, rest, cycles }) => {
+            if let Some(id) = controllers::find_task(&task) {
+                controllers::focus(id, work, rest, cycles);
+            }
+        }
+        Some(Commands::Stats) => controllers::show_stats(),
+        Some(Commands::Template { action }) => match action {
+            TemplateCommands::List => controllers::list_templates(),
+            TemplateCommands::Save { name, tasks } => controllers::save_template(name, tasks),
+            TemplateCommands::Remove { name } => controllers::remove_template(name),
+            TemplateCommands::Use { name, values } => controllers::use_template(name, values),
+        },
+        Some(Commands::Compact) => controllers::compact(),
+        Some(Commands::Encrypt) => controllers::encrypt_store(env::var("TASK_PASSPHRASE").ok()),
+        Some(Commands::Decrypt) => controllers::decrypt_store(),
+        None => router::router(),
+    }
 }
@@ -1,8 +1,106 @@
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDateTime, Weekday};
 use serde::{Serialize, Deserialize};
+use crate::i18n::t;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct Task {
     pub id: u32,
     pub description: String,
     pub completed: bool,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    #[serde(default)]
+    pub due: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub pomodoros: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Recurrence {
+    Daily,
+    Weekly(Weekday),
+    Monthly(u32),
+}
+
+/// Tasks created together, with `{placeholders}` filled in when the template is used.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Template {
+    pub name: String,
+    pub tasks: Vec<String>,
+}
+
+/// Whether an attachment is an URL, or a path to a file that is still there.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AttachmentStatus {
+    Link,
+    Present,
+    Missing,
+}
+
+/// One line of the append-only task log.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Event {
+    pub at: DateTime<Local>,
+    /// Position of the event in the log, counted across compactions. Events logged before it
+    /// was recorded are numbered when they are read.
+    #[serde(default)]
+    pub sequence: u64,
+    pub change: Change,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Change {
+    Created(Task),
+    Edited(Task),
+    Completed(u32),
+    Deleted(u32),
+    Pomodoro(u32),
+}
+
+
+impl Change {
+    /// Changes completing a task. A recurring task is completed, then reopened at its next occurrence,
+    /// so that completing it runs the `completed` hooks and shows in its history like any other.
+    pub fn completion(task: &Task) -> Vec<Change> {
+
+        match (task.recurrence, task.due) {
+            (Some(recurrence), Some(due)) => {
+                let mut next = task.clone();
+                next.completed = false;
+                next.due = Some(recurrence.next_after(due));
+                vec![Change::Completed(task.id), Change::Edited(next)]
+            }
+            _ => vec![Change::Completed(task.id)],
+        }
+    }
+}
+
+
+impl AttachmentStatus {
+    pub fn of(reference: &str) -> AttachmentStatus {
+
+        if reference.starts_with("http://") || reference.starts_with("https://") {
+            AttachmentStatus::Link
+        } else if Path::new(reference).exists() {
+            AttachmentStatus::Present
+        } else {
+            AttachmentStatus::Missing
+        }
+    }
+
+
+    /// Label shown before the attachment, padded to the same width for every status.
+    pub fn label(&self) -> &'static str {
+
+        match self {
+            AttachmentStatus::Link => t("attachment_link"),
+            AttachmentStatus::Present => t("attachment_ok"),
+            AttachmentStatus::Missing => t("attachment_missing"),
+        }
+    }
+}
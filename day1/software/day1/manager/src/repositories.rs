@@ -1,19 +1,534 @@
-use std::fs;
-use crate::models::Task;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::sync::OnceLock;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use crate::crypto::{CryptoManager, SALT_LEN};
+use crate::hooks;
+use crate::models::{Change, Event, Task};
 
 
+/// State of the tasks at the last compaction.
+const SNAPSHOT_FILE: &str = "data/tasks.json";
+
+/// Every change made since the last compaction, one JSON event per line.
+const LOG_FILE: &str = "data/tasks.log";
+
+/// Encrypted snapshot: the key salt followed by the encrypted JSON.
+const ENCRYPTED_SNAPSHOT_FILE: &str = "data/tasks.json.enc";
+
+/// Encrypted log: one hex-encoded encrypted event per line.
+const ENCRYPTED_LOG_FILE: &str = "data/tasks.log.enc";
+
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+static CRYPTO: OnceLock<CryptoManager> = OnceLock::new();
+
+
+/// Tasks at the last compaction, with the sequence number of the last event folded into them:
+/// the events up to it are skipped if the log could not be emptied.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    sequence: u64,
+    tasks: Vec<Task>,
+}
+
+
+/// Current tasks, rebuilt by replaying the log on top of the snapshot.
 pub fn read()-> Vec<Task>{
 
-    let list_str = fs::read_to_string("data/tasks.json").expect("File not found");
+    replay().into_iter().map(|(task, _)| task).collect()
+}
 
-    let list = serde_json::from_str(&list_str).expect("Error reading JSON");
 
-    return list;
+/// Current task with the events recorded for it since the last compaction.
+pub fn history(task_id: u32) -> Option<(Task, Vec<Event>)> {
+
+    replay().into_iter().find(|(task, _)| task.id == task_id)
 }
 
 
+/// Records the changes in the log, unless a pre-hook vetoes one of them, in which case nothing is written.
+pub fn append(changes: Vec<Change>) -> Result<(), String> {
+
+    let at = Local::now();
+    let mut timeline = replay();
+    let mut events = Vec::new();
+    let mut notifications = Vec::new();
+
+    for (sequence, change) in (last_sequence() + 1..).zip(changes) {
+        let event = Event { at, sequence, change };
+        let (name, task) = describe(&mut timeline, &event);
+        if let Some(task) = &task {
+            hooks::before(name, task)?;
+        }
+        events.push(event);
+        notifications.push((name, task));
+    }
+
+    let log_file = if is_encrypted() {ENCRYPTED_LOG_FILE} else {LOG_FILE};
+    let mut log = OpenOptions::new().create(true).append(true).open(log_file).expect("Failed to open log");
+
+    for event in events {
+        let line = serde_json::to_string(&event).expect("Error serializing");
+        writeln!(log, "{}", seal_line(line)).expect("Failed to write");
+    }
+
+    for (name, task) in notifications {
+        if let Some(task) = task {
+            hooks::after(name, &task);
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Folds the log into a new snapshot and empties it, returning the number of events compacted.
+pub fn compact() -> usize {
+
+    let folded = read_snapshot().sequence;
+    let events = read_log().iter().filter(|event| event.sequence > folded).count();
+    let log_file = if is_encrypted() {ENCRYPTED_LOG_FILE} else {LOG_FILE};
+
+    write_snapshot(&Snapshot { sequence: last_sequence(), tasks: read() });
+    fs::write(log_file, "").expect("Failed to write");
+
+    events
+}
+
+
+/// Replaces the tasks of the snapshot. The events logged since the last compaction still apply on top of them.
 pub fn write(new: Vec<Task>) {
 
-    let tasks = serde_json::to_string_pretty(&new).expect("Error serializing");
-    fs::write("data/tasks.json", tasks).expect("Failed to write");
-}
\ No newline at end of file
+    write_snapshot(&Snapshot { sequence: read_snapshot().sequence, tasks: new });
+}
+
+
+pub fn is_encrypted() -> bool {
+
+    Path::new(ENCRYPTED_SNAPSHOT_FILE).exists()
+}
+
+
+/// Gives the passphrase used to derive the key of an encrypted store.
+pub fn unlock(passphrase: String) {
+
+    let _ = PASSPHRASE.set(passphrase);
+}
+
+
+/// Checks a passphrase against the encrypted snapshot without unlocking the store.
+pub fn verify(passphrase: &str) -> Result<(), String> {
+
+    let sealed = fs::read(ENCRYPTED_SNAPSHOT_FILE).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if sealed.len() < SALT_LEN {
+        return Err("Corrupted data".to_string());
+    }
+
+    let crypto = CryptoManager::new(passphrase, &sealed[..SALT_LEN])?;
+    crypto.decrypt(&sealed[SALT_LEN..]).map(|_| ())
+}
+
+
+/// Replaces the plaintext snapshot and log with encrypted copies keyed on `passphrase`.
+pub fn encrypt(passphrase: &str) {
+
+    let snapshot = read_snapshot();
+    let events = read_log();
+    let salt = CryptoManager::generate_salt();
+    let crypto = CryptoManager::new(passphrase, &salt).unwrap_or_else(|e| fail(e));
+
+    let mut sealed = salt.to_vec();
+    sealed.extend(crypto.encrypt(&serde_json::to_string_pretty(&snapshot).expect("Error serializing")).unwrap_or_else(|e| fail(e)));
+
+    let mut log = String::new();
+    for event in events {
+        let line = serde_json::to_string(&event).expect("Error serializing");
+        log.push_str(&hex::encode(crypto.encrypt(&line).unwrap_or_else(|e| fail(e))));
+        log.push('\n');
+    }
+
+    replace(ENCRYPTED_SNAPSHOT_FILE, sealed);
+    replace(ENCRYPTED_LOG_FILE, log);
+    fs::remove_file(SNAPSHOT_FILE).expect("Failed to remove plaintext snapshot");
+    if Path::new(LOG_FILE).exists() {
+        fs::remove_file(LOG_FILE).expect("Failed to remove plaintext log");
+    }
+}
+
+
+/// Turns an encrypted store back into a plaintext snapshot and log.
+pub fn decrypt() {
+
+    let snapshot = read_snapshot();
+    let events = read_log();
+
+    let mut log = String::new();
+    for event in events {
+        log.push_str(&serde_json::to_string(&event).expect("Error serializing"));
+        log.push('\n');
+    }
+
+    replace(SNAPSHOT_FILE, serde_json::to_string_pretty(&snapshot).expect("Error serializing"));
+    replace(LOG_FILE, log);
+    fs::remove_file(ENCRYPTED_SNAPSHOT_FILE).expect("Failed to remove encrypted snapshot");
+    if Path::new(ENCRYPTED_LOG_FILE).exists() {
+        fs::remove_file(ENCRYPTED_LOG_FILE).expect("Failed to remove encrypted log");
+    }
+}
+
+
+/// Snapshot of the store. Snapshots written before the sequence numbers are a bare list of tasks.
+fn read_snapshot() -> Snapshot {
+
+    let list_str = if is_encrypted() {
+        let sealed = fs::read(ENCRYPTED_SNAPSHOT_FILE).expect("File not found");
+        if sealed.len() < SALT_LEN {
+            fail("Corrupted data".to_string());
+        }
+        crypto().decrypt(&sealed[SALT_LEN..]).unwrap_or_else(|e| fail(e))
+    } else {
+        fs::read_to_string(SNAPSHOT_FILE).expect("File not found")
+    };
+
+    serde_json::from_str(&list_str)
+        .or_else(|_| serde_json::from_str(&list_str).map(|tasks| Snapshot { sequence: 0, tasks }))
+        .expect("Error reading JSON")
+}
+
+
+/// Writes the snapshot in place of the current one, in a single step.
+fn write_snapshot(snapshot: &Snapshot) {
+
+    let json = serde_json::to_string_pretty(snapshot).expect("Error serializing");
+
+    if is_encrypted() {
+        let mut sealed = read_salt();
+        sealed.extend(crypto().encrypt(&json).unwrap_or_else(|e| fail(e)));
+        replace(ENCRYPTED_SNAPSHOT_FILE, sealed);
+    } else {
+        replace(SNAPSHOT_FILE, json);
+    }
+}
+
+
+/// Writes a temporary file and renames it over `path`, so that a crash leaves either the old or the new content.
+fn replace(path: &str, content: impl AsRef<[u8]>) {
+
+    let temp = format!("{}.tmp", path);
+    let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(&temp).expect("Failed to write");
+
+    file.write_all(content.as_ref()).and_then(|_| file.sync_all()).expect("Failed to write");
+    fs::rename(&temp, path).expect("Failed to write");
+}
+
+
+fn read_log() -> Vec<Event> {
+
+    let log_file = if is_encrypted() {ENCRYPTED_LOG_FILE} else {LOG_FILE};
+
+    if !Path::new(log_file).exists() {
+        return Vec::new();
+    }
+
+    let log = fs::read_to_string(log_file).expect("Failed to read log");
+    let mut last = 0;
+
+    log.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut event: Event = serde_json::from_str(&open_line(line)).expect("Error reading log");
+            if event.sequence == 0 {
+                event.sequence = last + 1;
+            }
+            last = event.sequence;
+            event
+        })
+        .collect()
+}
+
+
+/// Sequence number of the last event recorded, in the log or folded into the snapshot.
+fn last_sequence() -> u64 {
+
+    let logged = read_log().last().map_or(0, |event| event.sequence);
+    logged.max(read_snapshot().sequence)
+}
+
+
+fn replay() -> Vec<(Task, Vec<Event>)> {
+
+    let snapshot = read_snapshot();
+    let mut timeline: Vec<(Task, Vec<Event>)> = snapshot.tasks.into_iter().map(|task| (task, Vec::new())).collect();
+
+    // Events already in the snapshot are still logged if a compaction stopped before emptying the log.
+    for event in read_log().into_iter().filter(|event| event.sequence > snapshot.sequence) {
+        apply(&mut timeline, event);
+    }
+
+    timeline
+}
+
+
+/// Hook event name of a change and the task it concerns, as it is after the change
+/// (or before it, for a deletion). The change is applied to `timeline`.
+fn describe(timeline: &mut Vec<(Task, Vec<Event>)>, event: &Event) -> (&'static str, Option<Task>) {
+
+    let find = |timeline: &Vec<(Task, Vec<Event>)>, task_id: u32| {
+        timeline.iter().find(|(task, _)| task.id == task_id).map(|(task, _)| task.clone())
+    };
+
+    let (name, task_id) = match &event.change {
+        Change::Created(task) => ("added", task.id),
+        Change::Edited(task) => ("edited", task.id),
+        Change::Completed(task_id) => ("completed", *task_id),
+        Change::Pomodoro(task_id) => ("edited", *task_id),
+        Change::Deleted(task_id) => ("deleted", *task_id),
+    };
+
+    let before = find(timeline, task_id);
+    apply(timeline, event.clone());
+
+    match event.change {
+        Change::Deleted(_) => (name, before),
+        _ => (name, find(timeline, task_id)),
+    }
+}
+
+
+fn apply(timeline: &mut Vec<(Task, Vec<Event>)>, event: Event) {
+
+    match &event.change {
+        Change::Created(task) => timeline.push((task.clone(), vec![event])),
+        Change::Edited(edited) => {
+            if let Some((task, events)) = timeline.iter_mut().find(|(task, _)| task.id == edited.id) {
+                *task = edited.clone();
+                events.push(event);
+            }
+        }
+        Change::Completed(task_id) => {
+            if let Some((task, events)) = timeline.iter_mut().find(|(task, _)| task.id == *task_id) {
+                task.completed = true;
+                events.push(event);
+            }
+        }
+        Change::Pomodoro(task_id) => {
+            if let Some((task, events)) = timeline.iter_mut().find(|(task, _)| task.id == *task_id) {
+                task.pomodoros += 1;
+                events.push(event);
+            }
+        }
+        Change::Deleted(task_id) => {
+            let task_id = *task_id;
+            timeline.retain(|(task, _)| task.id != task_id);
+            for (task, _) in timeline.iter_mut() {
+                if task.id > task_id {
+                    task.id -= 1;
+                }
+            }
+        }
+    }
+}
+
+
+fn seal_line(line: String) -> String {
+
+    if is_encrypted() {
+        hex::encode(crypto().encrypt(&line).unwrap_or_else(|e| fail(e)))
+    } else {
+        line
+    }
+}
+
+
+fn open_line(line: &str) -> String {
+
+    if is_encrypted() {
+        let sealed = hex::decode(line.trim()).unwrap_or_else(|e| fail(format!("Corrupted log: {}", e)));
+        crypto().decrypt(&sealed).unwrap_or_else(|e| fail(e))
+    } else {
+        line.to_string()
+    }
+}
+
+
+fn read_salt() -> Vec<u8> {
+
+    let sealed = fs::read(ENCRYPTED_SNAPSHOT_FILE).expect("File not found");
+    sealed.get(..SALT_LEN).unwrap_or_else(|| fail("Corrupted data".to_string())).to_vec()
+}
+
+
+/// Key of the encrypted store, derived once from the passphrase given to `unlock`.
+fn crypto() -> &'static CryptoManager {
+
+    CRYPTO.get_or_init(|| {
+        let passphrase = PASSPHRASE.get().unwrap_or_else(|| fail("The task store is encrypted, no passphrase given".to_string()));
+        CryptoManager::new(passphrase, &read_salt()).unwrap_or_else(|e| fail(e))
+    })
+}
+
+
+fn fail(message: String) -> ! {
+
+    eprintln!("\n{}\n", message);
+    process::exit(1);
+}
+
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+    use chrono::NaiveDateTime;
+    use tempfile::TempDir;
+    use crate::models::Recurrence;
+
+    /// The store lives in the working directory, shared by the whole process.
+    static STORE: Mutex<()> = Mutex::new(());
+
+    /// An empty store in a new working directory, kept to the calling test until the guard is dropped.
+    fn store() -> (MutexGuard<'static, ()>, TempDir) {
+        let guard = STORE.lock().unwrap_or_else(PoisonError::into_inner);
+        let dir = tempfile::tempdir().unwrap();
+        env::set_current_dir(dir.path()).unwrap();
+        fs::create_dir_all("data/hooks").unwrap();
+        fs::write(SNAPSHOT_FILE, "[]").unwrap();
+        (guard, dir)
+    }
+
+    fn task(id: u32, description: &str) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            completed: false,
+            notes: String::new(),
+            attachments: Vec::new(),
+            due: None,
+            recurrence: None,
+            pomodoros: 0,
+        }
+    }
+
+    fn descriptions() -> Vec<(u32, String)> {
+        read().into_iter().map(|task| (task.id, task.description)).collect()
+    }
+
+    fn hook(name: &str, script: &str) {
+        let path = Path::new("data/hooks").join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn completing_a_recurring_task_runs_the_completed_hooks() {
+        let (_store, _dir) = store();
+
+        let due = NaiveDateTime::parse_from_str("2026-10-14 09:00", "%Y-%m-%d %H:%M").unwrap();
+        let task = Task { due: Some(due), recurrence: Some(Recurrence::Daily), ..task(1, "Water plants") };
+        append(vec![Change::Created(task.clone())]).unwrap();
+        let log = fs::read_to_string(LOG_FILE).unwrap();
+
+        hook("pre-completed", "#!/bin/sh\nexit 1\n");
+        assert!(append(Change::completion(&task)).is_err());
+        assert_eq!(fs::read_to_string(LOG_FILE).unwrap(), log);
+
+        hook("pre-completed", "#!/bin/sh\nexit 0\n");
+        hook("post-completed", "#!/bin/sh\ntouch data/completed\n");
+        append(Change::completion(&task)).unwrap();
+        assert!(Path::new("data/completed").exists());
+
+        let (task, events) = history(1).unwrap();
+        assert!(!task.completed);
+        assert_eq!(task.due, Some(due + chrono::Duration::days(1)));
+        assert!(matches!(events[..], [_, Event { change: Change::Completed(1), .. }, Event { change: Change::Edited(_), .. }]));
+    }
+
+    #[test]
+    fn replays_and_compacts_the_log() {
+        let (_store, _dir) = store();
+
+        append(vec![Change::Created(task(1, "Buy milk")), Change::Created(task(2, "Call mom")), Change::Created(task(3, "Pay rent"))]).unwrap();
+        append(vec![Change::Edited(task(3, "Pay the rent")), Change::Deleted(1)]).unwrap();
+
+        let expected = vec![(1, "Call mom".to_string()), (2, "Pay the rent".to_string())];
+        assert_eq!(descriptions(), expected);
+        let (_, events) = history(2).unwrap();
+        assert!(matches!(events[..], [Event { sequence: 3, change: Change::Created(_), .. }, Event { sequence: 4, change: Change::Edited(_), .. }]));
+
+        let log = fs::read_to_string(LOG_FILE).unwrap();
+        assert_eq!(compact(), 5);
+        assert_eq!(fs::read_to_string(LOG_FILE).unwrap(), "");
+        assert_eq!(descriptions(), expected);
+        assert!(history(2).unwrap().1.is_empty());
+
+        // A compaction stopped before emptying the log leaves events already in the snapshot.
+        fs::write(LOG_FILE, log).unwrap();
+        assert_eq!(descriptions(), expected);
+
+        append(vec![Change::Completed(1)]).unwrap();
+        let (task, events) = history(1).unwrap();
+        assert!(task.completed);
+        assert!(matches!(events[..], [Event { sequence: 6, change: Change::Completed(1), .. }]));
+
+        assert_eq!(compact(), 1);
+        assert_eq!(descriptions(), expected);
+        assert!(read()[0].completed);
+    }
+
+    #[test]
+    fn numbers_the_events_of_older_logs() {
+        let (_store, _dir) = store();
+
+        let created = serde_json::json!({ "at": Local::now(), "change": { "Created": task(1, "Buy milk") } });
+        let completed = serde_json::json!({ "at": Local::now(), "change": { "Completed": 1 } });
+        fs::write(LOG_FILE, format!("{}\n{}\n", created, completed)).unwrap();
+
+        assert_eq!(history(1).unwrap().1.iter().map(|event| event.sequence).collect::<Vec<_>>(), [1, 2]);
+        append(vec![Change::Created(task(2, "Call mom"))]).unwrap();
+        assert_eq!(history(2).unwrap().1[0].sequence, 3);
+
+        assert_eq!(compact(), 3);
+        assert_eq!(descriptions(), [(1, "Buy milk".to_string()), (2, "Call mom".to_string())]);
+    }
+
+    // The key is derived once per process, so this is the only test of an encrypted store.
+    #[test]
+    fn encrypts_and_decrypts_the_store() {
+        let (_store, _dir) = store();
+
+        append(vec![Change::Created(task(1, "Buy milk")), Change::Created(task(2, "Call mom"))]).unwrap();
+        compact();
+        append(vec![Change::Edited(task(2, "Call dad"))]).unwrap();
+        let expected = vec![(1, "Buy milk".to_string()), (2, "Call dad".to_string())];
+
+        encrypt("correct horse");
+        assert!(is_encrypted());
+        assert!(!Path::new(SNAPSHOT_FILE).exists() && !Path::new(LOG_FILE).exists());
+        for file in [ENCRYPTED_SNAPSHOT_FILE, ENCRYPTED_LOG_FILE] {
+            assert!(!String::from_utf8_lossy(&fs::read(file).unwrap()).contains("Call"));
+        }
+
+        assert!(verify("wrong horse").is_err());
+        assert!(verify("correct horse").is_ok());
+
+        unlock("correct horse".to_string());
+        assert_eq!(descriptions(), expected);
+        append(vec![Change::Completed(1)]).unwrap();
+        assert!(!fs::read_to_string(ENCRYPTED_LOG_FILE).unwrap().contains("Completed"));
+        assert!(read()[0].completed);
+
+        decrypt();
+        assert!(!is_encrypted());
+        assert!(!Path::new(ENCRYPTED_LOG_FILE).exists());
+        assert_eq!(descriptions(), expected);
+        assert!(read()[0].completed);
+        assert_eq!(history(2).unwrap().1.len(), 1);
+        assert_eq!(compact(), 2);
+    }
+}
@@ -1,15 +1,17 @@
 use std::io;
 use crate::controllers;
+use crate::views;
+use task::i18n::t;
 
 
 pub fn router(){
 
-    println!("\nWelcome to your Task Manager!");
+    println!("\n{}", t("welcome"));
 
     let mut menu = true;
 
     loop {
-        if menu {println!("\nWhat do you want to do?\n1 - List all tasks\n2 - Add a task\n3 - Edit a task\n4 - Mark a task\n5 - Delete a task\n6 - Leave\n");}
+        if menu {println!("\n{}\n", t("menu"));}
 
         let mut rep = String::new();
         io::stdin().read_line(&mut rep).expect("Failed to read line");
@@ -30,20 +32,50 @@ pub fn router(){
             }
             "4" => {
                 controllers::list_tasks();
-                controllers::mark_task();
+                controllers::mark_tasks(views::ask_task_to_mark(), false);
                 menu = true;
             }
             "5" => {
                 controllers::list_tasks();
-                controllers::delete_task();
+                controllers::delete_tasks(views::ask_task_to_delete(), false);
                 menu = true;
             }
-            "6" => break,
+            "6" => {
+                controllers::list_tasks();
+                controllers::show_task(views::ask_task_to_show());
+                menu = true;
+            }
+            "7" => {
+                controllers::list_tasks();
+                controllers::edit_notes(views::ask_task_to_annotate());
+                menu = true;
+            }
+            "8" => {
+                controllers::list_tasks();
+                let task_id = views::ask_task_to_attach();
+                controllers::attach_file(task_id, views::ask_attachment());
+                menu = true;
+            }
+            "9" => {
+                controllers::list_tasks();
+                controllers::show_history(views::ask_task_to_trace());
+                menu = true;
+            }
+            "10" => {
+                controllers::list_tasks();
+                controllers::focus(views::ask_task_to_focus(), 25, 5, 4);
+                menu = true;
+            }
+            "11" => {
+                controllers::show_stats();
+                menu = true;
+            }
+            "12" => break,
             _ => {
-                println!("\nType 1, 2, 3, 4, 5 or 6.\n");
+                println!("\n{}\n", t("menu_invalid"));
                 menu = false;
             }
         }
     }
-    println!("\nSee you!\n")
+    println!("\n{}\n", t("goodbye"))
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use std::ops::RangeInclusive;
+use crate::i18n::t;
+use crate::models::Task;
+
+
+/// Tasks picked by the user, either by ids (`1-5,8,12`), by a filter expression
+/// (`all`, `done`, `todo` or `text:<fragment>`) or by a fuzzy match on the description.
+pub enum Selection {
+    Ids(Vec<RangeInclusive<u32>>),
+    All,
+    Completed,
+    Pending,
+    Text(String),
+    Fuzzy(String),
+}
+
+
+impl Selection {
+    pub fn parse(input: &str) -> Result<Selection, String> {
+
+        let input = input.trim();
+
+        match input {
+            "" => return Err(t("empty_selection").to_string()),
+            "all" => return Ok(Selection::All),
+            "done" => return Ok(Selection::Completed),
+            "todo" => return Ok(Selection::Pending),
+            _ => {}
+        }
+
+        if let Some(fragment) = input.strip_prefix("text:") {
+            return Ok(Selection::Text(fragment.trim().to_lowercase()));
+        }
+
+        if !input.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '-' | ' ')) {
+            return Ok(Selection::Fuzzy(input.to_lowercase()));
+        }
+
+        let mut ranges = Vec::new();
+
+        for part in input.split(',') {
+            let part = part.trim();
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let start = parse_id(start)?;
+                    let end = parse_id(end)?;
+                    if start > end {
+                        return Err(t("invalid_range").replace("{range}", part));
+                    }
+                    ranges.push(start..=end);
+                }
+                None => {
+                    let id = parse_id(part)?;
+                    ranges.push(id..=id);
+                }
+            }
+        }
+
+        // Ranges are kept as such rather than expanded, so that "1-4294967295" costs no more than "1".
+        ranges.sort_by_key(|range| *range.start());
+        let mut ids: Vec<RangeInclusive<u32>> = Vec::new();
+        for range in ranges {
+            match ids.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=*last.end().max(range.end());
+                }
+                _ => ids.push(range),
+            }
+        }
+
+        Ok(Selection::Ids(ids))
+    }
+
+
+    pub fn matches(&self, task: &Task) -> bool {
+
+        match self {
+            Selection::Ids(ids) => ids.iter().any(|range| range.contains(&task.id)),
+            Selection::All => true,
+            Selection::Completed => task.completed,
+            Selection::Pending => !task.completed,
+            Selection::Text(fragment) => task.description.to_lowercase().contains(fragment),
+            Selection::Fuzzy(query) => fuzzy_score(query, &task.description).is_some(),
+        }
+    }
+}
+
+
+/// Tasks whose description fuzzy-matches `query`, best match first.
+pub fn rank<'a>(query: &str, tasks: &'a [Task]) -> Vec<&'a Task> {
+
+    let mut scored: Vec<(i32, &Task)> = tasks.iter()
+        .filter_map(|task| fuzzy_score(query, &task.description).map(|score| (score, task)))
+        .collect();
+
+    scored.sort_by(|(a, first), (b, second)| b.cmp(a).then(first.id.cmp(&second.id)));
+    scored.into_iter().map(|(_, task)| task).collect()
+}
+
+
+/// The candidate to pick without asking: the only one, or the only one containing `query` as is.
+pub fn unique_match<'a>(query: &str, candidates: &[&'a Task]) -> Option<&'a Task> {
+
+    if let [task] = candidates {
+        return Some(task);
+    }
+
+    let mut exact = candidates.iter().filter(|task| task.description.to_lowercase().contains(query));
+    match (exact.next(), exact.next()) {
+        (Some(task), None) => Some(task),
+        _ => None,
+    }
+}
+
+
+/// Scores `text` against `query` when every character of the query appears in order,
+/// favouring consecutive characters and matches at the start of words.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|c| *c == wanted)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if let Some(previous) = previous {
+            score -= (found - previous - 1).min(3) as i32;
+        }
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+
+fn parse_id(input: &str) -> Result<u32, String> {
+
+    input.trim().parse::<u32>().map_err(|_| t("invalid_id").replace("{id}", input.trim()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(input: &str) -> Vec<RangeInclusive<u32>> {
+        match Selection::parse(input) {
+            Ok(Selection::Ids(ids)) => ids,
+            _ => panic!("\"{}\" is not parsed as ids", input),
+        }
+    }
+
+    fn task(id: u32, description: &str) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            completed: false,
+            notes: String::new(),
+            attachments: Vec::new(),
+            due: None,
+            recurrence: None,
+            pomodoros: 0,
+        }
+    }
+
+    #[test]
+    fn parses_ids_and_ranges() {
+        assert_eq!(ids("1-5,8, 12"), vec![1..=5, 8..=8, 12..=12]);
+    }
+
+    #[test]
+    fn merges_duplicate_and_overlapping_ids() {
+        assert_eq!(ids("3,1-4,2,4,5,9-10,7-9"), vec![1..=5, 7..=10]);
+    }
+
+    #[test]
+    fn keeps_huge_ranges_unexpanded() {
+        let selection = Selection::parse("1-4294967295").unwrap();
+
+        assert!(selection.matches(&task(u32::MAX, "Last")));
+        assert!(!selection.matches(&task(0, "First")));
+    }
+
+    #[test]
+    fn rejects_reversed_ranges_and_invalid_ids() {
+        assert!(Selection::parse("5-1").is_err());
+        assert!(Selection::parse("1,,2").is_err());
+        assert!(Selection::parse("99999999999").is_err());
+        assert!(Selection::parse("  ").is_err());
+    }
+
+    #[test]
+    fn parses_filters() {
+        assert!(matches!(Selection::parse("all"), Ok(Selection::All)));
+        assert!(matches!(Selection::parse("done"), Ok(Selection::Completed)));
+        assert!(matches!(Selection::parse("todo"), Ok(Selection::Pending)));
+    }
+
+    #[test]
+    fn parses_text_fragments() {
+        let selection = Selection::parse("text: Rent").unwrap();
+
+        assert!(matches!(&selection, Selection::Text(fragment) if fragment == "rent"));
+        assert!(selection.matches(&task(1, "Pay rent")));
+        assert!(!selection.matches(&task(2, "Pay the plumber")));
+    }
+
+    #[test]
+    fn falls_back_to_fuzzy_matching() {
+        let selection = Selection::parse("Grcries").unwrap();
+
+        assert!(matches!(&selection, Selection::Fuzzy(query) if query == "grcries"));
+        assert!(selection.matches(&task(1, "Buy groceries")));
+        assert!(!selection.matches(&task(2, "Call supplier")));
+    }
+
+    #[test]
+    fn ranks_closer_fuzzy_matches_first() {
+        let tasks = [task(1, "Read the report"), task(2, "Report taxes")];
+        let ranked = rank("report", &tasks);
+
+        assert_eq!(ranked.iter().map(|task| task.id).collect::<Vec<_>>(), vec![2, 1]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::models::Template;
+
+
+/// Named groups of task descriptions, kept next to the task store.
+const TEMPLATES_FILE: &str = "data/templates.json";
+
+
+pub fn read() -> Vec<Template> {
+
+    if !Path::new(TEMPLATES_FILE).exists() {
+        return Vec::new();
+    }
+
+    let templates = fs::read_to_string(TEMPLATES_FILE).expect("Failed to read templates");
+    serde_json::from_str(&templates).expect("Error reading JSON")
+}
+
+
+pub fn find(name: &str) -> Option<Template> {
+
+    read().into_iter().find(|template| template.name == name)
+}
+
+
+/// Saves a template, replacing the one with the same name if any.
+pub fn save(template: Template) {
+
+    let mut templates = read();
+    templates.retain(|other| other.name != template.name);
+    templates.push(template);
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    write(templates);
+}
+
+
+/// Removes a template, returning false if there was none with this name.
+pub fn remove(name: &str) -> bool {
+
+    let mut templates = read();
+    let count = templates.len();
+    templates.retain(|template| template.name != name);
+
+    if templates.len() == count {
+        return false;
+    }
+
+    write(templates);
+    true
+}
+
+
+/// Names of the `{placeholders}` used in the template, in order of first appearance.
+pub fn placeholders(template: &Template) -> Vec<String> {
+
+    let mut names: Vec<String> = Vec::new();
+
+    for description in &template.tasks {
+        substitute(description, |name| {
+            if !names.iter().any(|other| other == name) {
+                names.push(name.to_string());
+            }
+            None
+        });
+    }
+
+    names
+}
+
+
+/// Descriptions of the template with every placeholder replaced by its value, in a single pass
+/// so that values are never themselves searched for placeholders.
+pub fn instantiate(template: &Template, values: &HashMap<String, String>) -> Vec<String> {
+
+    template.tasks.iter()
+        .map(|description| substitute(description, |name| values.get(name).cloned()))
+        .collect()
+}
+
+
+/// Replaces each `{name}` of the description by `value(name)`, keeping it as is when there is none.
+/// A brace without a name, like `{}` or an unclosed `{`, is plain text.
+fn substitute(description: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+
+    let mut output = String::new();
+    let mut rest = description;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        match after.find(['{', '}']) {
+            Some(end) if end > 0 && after[end..].starts_with('}') => {
+                match value(&after[..end]) {
+                    Some(value) => output.push_str(&value),
+                    None => output.push_str(&rest[start..start + end + 2]),
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+
+fn write(templates: Vec<Template>) {
+
+    let templates = serde_json::to_string_pretty(&templates).expect("Error serializing");
+    fs::write(TEMPLATES_FILE, templates).expect("Failed to write");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(tasks: &[&str]) -> Template {
+        Template { name: "release".to_string(), tasks: tasks.iter().map(|task| task.to_string()).collect() }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn lists_placeholders_in_order_of_first_appearance() {
+        let template = template(&["Tag {version} of {project}", "Announce {project} {version}", "Thank {team}"]);
+
+        assert_eq!(placeholders(&template), ["version", "project", "team"]);
+    }
+
+    #[test]
+    fn ignores_braces_without_a_name() {
+        let template = template(&["Empty {} braces", "Unclosed {version", "Nested {a {b}", "Closing } only"]);
+
+        assert_eq!(placeholders(&template), ["b"]);
+        assert_eq!(instantiate(&template, &values(&[("b", "B"), ("version", "1.0")])), [
+            "Empty {} braces", "Unclosed {version", "Nested {a B", "Closing } only",
+        ]);
+    }
+
+    #[test]
+    fn replaces_repeated_placeholders() {
+        let template = template(&["{project} {version}: build {project}"]);
+
+        assert_eq!(instantiate(&template, &values(&[("project", "task"), ("version", "2.1")])), ["task 2.1: build task"]);
+    }
+
+    #[test]
+    fn does_not_substitute_inside_values() {
+        let template = template(&["Release {version} {codename}"]);
+        // Each map iterates in its own random order.
+        for _ in 0..10 {
+            let values = values(&[("version", "{codename}"), ("codename", "x")]);
+            assert_eq!(instantiate(&template, &values), ["Release {codename} x"]);
+        }
+    }
+
+    #[test]
+    fn keeps_placeholders_without_a_value() {
+        let template = template(&["Ship {version} on {date}"]);
+
+        assert_eq!(instantiate(&template, &values(&[("version", "3")])), ["Ship 3 on {date}"]);
+    }
+}
@@ -1,92 +1,531 @@
-use std::io;
-use crate::models::Task;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::thread;
+use std::time::Duration;
+use chrono::{Datelike, NaiveDateTime};
+use task::dates::Interpretation;
+use task::i18n::{self, t};
+use task::models::{AttachmentStatus, Change, Event, Task, Template};
+use task::selection::Selection;
 
 
 pub fn display_list(list: Vec<Task>) {
 
-    println!("\nHere are your tasks:");
+    println!("\n{}", t("list_header"));
 
     for task in list {
-        if task.completed == false {println!("-- {} -- [ ] {}", task.id, task.description)} else {println!("-- {} -- [X] {}", task.id, task.description)};
+        if !task.completed {println!("-- {} -- [ ] {}{}", task.id, task.description, due_label(&task))} else {println!("-- {} -- [X] {}{}", task.id, task.description, due_label(&task))};
     }
 }
 
 
 pub fn ask_description()-> std::string::String{
 
-    println!("\nPlease enter a description:\n");
+    println!("\n{}\n", t("ask_description"));
 
     let mut description = String::new();
     io::stdin().read_line(&mut description).expect("Failed to read line");
 
-    return description.trim().to_string();
+    description.trim().to_string()
 }
 
 
-pub fn ask_task_to_edit() -> u32{
+/// Shows how a description was understood and asks whether to keep the due date.
+pub fn confirm_interpretation(interpretation: &Interpretation) -> bool{
 
-    println!("\nWhich one do you want to edit?\n");
+    println!("\n{}{}", t("label_description"), interpretation.description);
+
+    if let Some(due) = interpretation.due {
+        println!("{}{} {}", t("label_due"), i18n::weekday(due.weekday()), due.format("%Y-%m-%d %H:%M"));
+    }
+    if let Some(recurrence) = interpretation.recurrence {
+        println!("{}{}", t("label_repeats"), recurrence);
+    }
+
+    println!("\n{}\n", t("confirm_interpretation"));
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).expect("Failed to read line");
+
+    !i18n::is_no(&answer)
+}
+
+
+fn due_label(task: &Task) -> String{
+
+    let pomodoros = match task.pomodoros {
+        0 => String::new(),
+        count => format!(" [{}]", t("pomodoros").replace("{count}", &count.to_string())),
+    };
+
+    match (task.due, task.recurrence) {
+        (Some(due), Some(recurrence)) => format!(" ({} {}, {}){}", t("due"), due.format("%Y-%m-%d %H:%M"), recurrence, pomodoros),
+        (Some(due), None) => format!(" ({} {}){}", t("due"), due.format("%Y-%m-%d %H:%M"), pomodoros),
+        _ => pomodoros,
+    }
+}
+
+
+pub fn ask_task_to_edit() -> Selection{
+
+    println!("\n{}\n", t("ask_edit"));
 
     loop {
         let mut task_to_edit = String::new();
         io::stdin().read_line(&mut task_to_edit).expect("Failed to read line");
-        
-        match task_to_edit.trim().parse::<u32>() {
-            Ok(id) => return id,
-            Err(_) => println!("\nType the id of the task you want to edit.\n")
+
+        match Selection::parse(&task_to_edit) {
+            Ok(selection) => return selection,
+            Err(e) => println!("\n{}. {}\n", e, t("retry_edit"))
+        }
+    }
+}
+
+
+/// Lets the user choose among tasks that all match what they typed, best match first.
+pub fn pick_task<'a>(candidates: &[&'a Task]) -> Option<&'a Task>{
+
+    println!("\n{}", t("pick_header"));
+
+    for (index, task) in candidates.iter().enumerate() {
+        println!("{} - [{}] {}", index + 1, task.id, task.description);
+    }
+
+    println!("\n{}\n", t("pick_prompt"));
+
+    loop {
+        let mut choice = String::new();
+        io::stdin().read_line(&mut choice).expect("Failed to read line");
+
+        if choice.trim().is_empty() {
+            return None;
+        }
+
+        match choice.trim().parse::<usize>() {
+            Ok(index) if (1..=candidates.len()).contains(&index) => return Some(candidates[index - 1]),
+            _ => println!("\n{}\n", t("pick_prompt"))
         }
     }
 }
 
 
-pub fn ask_task_to_mark() -> u32{
+pub fn ask_task_to_mark() -> Selection{
 
-    println!("\nWhich one do you want to mark?\n");
+    println!("\n{}\n", t("ask_mark"));
 
     loop {
         let mut task_to_mark = String::new();
         io::stdin().read_line(&mut task_to_mark).expect("Failed to read line");
-        
-        match task_to_mark.trim().parse::<u32>() {
-            Ok(id) => return id,
-            Err(_) => println!("\nType the id of the task you want to mark.\n")
+
+        match Selection::parse(&task_to_mark) {
+            Ok(selection) => return selection,
+            Err(e) => println!("\n{}. {}\n", e, t("retry_mark"))
         }
     }
 }
 
 
-pub fn ask_task_to_delete() -> u32{
+pub fn ask_task_to_delete() -> Selection{
 
-    println!("\nWhich one do you want to delete?\n");
+    println!("\n{}\n", t("ask_delete"));
 
     loop {
         let mut task_to_delete = String::new();
         io::stdin().read_line(&mut task_to_delete).expect("Failed to read line");
-        
-        match task_to_delete.trim().parse::<u32>() {
+
+        match Selection::parse(&task_to_delete) {
+            Ok(selection) => return selection,
+            Err(e) => println!("\n{}. {}\n", e, t("retry_delete"))
+        }
+    }
+}
+
+
+pub fn ask_task_to_show() -> u32{
+
+    println!("\n{}\n", t("ask_show"));
+
+    loop {
+        let mut task_to_show = String::new();
+        io::stdin().read_line(&mut task_to_show).expect("Failed to read line");
+
+        match task_to_show.trim().parse::<u32>() {
+            Ok(id) => return id,
+            Err(_) => println!("\n{}\n", t("retry_show"))
+        }
+    }
+}
+
+
+pub fn ask_task_to_annotate() -> u32{
+
+    println!("\n{}\n", t("ask_annotate"));
+
+    loop {
+        let mut task_to_annotate = String::new();
+        io::stdin().read_line(&mut task_to_annotate).expect("Failed to read line");
+
+        match task_to_annotate.trim().parse::<u32>() {
+            Ok(id) => return id,
+            Err(_) => println!("\n{}\n", t("retry_annotate"))
+        }
+    }
+}
+
+
+pub fn ask_task_to_attach() -> u32{
+
+    println!("\n{}\n", t("ask_attach"));
+
+    loop {
+        let mut task_to_attach = String::new();
+        io::stdin().read_line(&mut task_to_attach).expect("Failed to read line");
+
+        match task_to_attach.trim().parse::<u32>() {
+            Ok(id) => return id,
+            Err(_) => println!("\n{}\n", t("retry_attach"))
+        }
+    }
+}
+
+
+pub fn ask_attachment() -> String{
+
+    println!("\n{}\n", t("ask_attachment"));
+
+    let mut attachment = String::new();
+    io::stdin().read_line(&mut attachment).expect("Failed to read line");
+
+    attachment.trim().to_string()
+}
+
+
+/// Opens the notes in `$VISUAL` or `$EDITOR` (falling back to `vi`) and returns the edited text.
+/// The current notes are kept if the editor cannot be run.
+pub fn ask_notes(current: &str) -> String{
+
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_else(|_| "vi".to_string());
+    let notes_file = create_notes_file(current);
+
+    let mut words = editor.split_whitespace();
+    let status = match words.next() {
+        Some(program) => Command::new(program).args(words).arg(&notes_file).status(),
+        None => Command::new("vi").arg(&notes_file).status(),
+    };
+
+    let notes = match status {
+        Ok(status) if status.success() => fs::read_to_string(&notes_file).expect("Failed to read notes file"),
+        _ => {
+            println!("\n{}", t("editor_failed").replace("{editor}", &editor));
+            current.to_string()
+        }
+    };
+    let _ = fs::remove_file(&notes_file);
+
+    notes.trim_end().to_string()
+}
+
+
+/// Creates a new temporary file holding the notes, with a name nobody can have created beforehand,
+/// readable by the user only since the notes may come from the encrypted store.
+fn create_notes_file(current: &str) -> PathBuf{
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    loop {
+        let notes_file = env::temp_dir().join(format!("task-notes-{}-{:016x}.md", process::id(), rand::random::<u64>()));
+
+        match options.open(&notes_file) {
+            Ok(mut file) => {
+                file.write_all(current.as_bytes()).expect("Failed to write notes file");
+                return notes_file;
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => panic!("Failed to create notes file: {}", e),
+        }
+    }
+}
+
+
+pub fn display_task(task: Task) {
+
+    if !task.completed {println!("\n-- {} -- [ ] {}{}", task.id, task.description, due_label(&task))} else {println!("\n-- {} -- [X] {}{}", task.id, task.description, due_label(&task))};
+
+    if task.notes.is_empty() {
+        println!("\n{}", t("no_notes"));
+    } else {
+        println!("\n{}", t("notes"));
+        for line in task.notes.lines() {
+            println!("    {}", line);
+        }
+    }
+
+    if task.attachments.is_empty() {
+        println!("\n{}", t("no_attachments"));
+    } else {
+        println!("\n{}", t("attachments"));
+        for attachment in task.attachments {
+            println!("    {} {}", AttachmentStatus::of(&attachment).label(), attachment);
+        }
+    }
+}
+
+
+pub fn confirm_bulk(action: &str, tasks: &[&Task]) -> bool{
+
+    let header = if action == "mark" {t("bulk_mark")} else {t("bulk_delete")};
+    println!("\n{}", header.replace("{count}", &tasks.len().to_string()));
+
+    for task in tasks {
+        if !task.completed {println!("-- {} -- [ ] {}", task.id, task.description)} else {println!("-- {} -- [X] {}", task.id, task.description)};
+    }
+
+    println!("\n{}\n", t("confirm_bulk"));
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).expect("Failed to read line");
+
+    i18n::is_yes(&answer)
+}
+
+
+pub fn ask_task_to_trace() -> u32{
+
+    println!("\n{}\n", t("ask_trace"));
+
+    loop {
+        let mut task_to_trace = String::new();
+        io::stdin().read_line(&mut task_to_trace).expect("Failed to read line");
+
+        match task_to_trace.trim().parse::<u32>() {
+            Ok(id) => return id,
+            Err(_) => println!("\n{}\n", t("retry_trace"))
+        }
+    }
+}
+
+
+pub fn display_history(task: Task, events: Vec<Event>) {
+
+    println!("\n{}", t("history_header").replace("{id}", &task.id.to_string()).replace("{description}", &task.description));
+
+    if events.is_empty() {
+        println!("{}", t("history_empty"));
+    }
+
+    for event in events {
+        let at = event.at.format("%Y-%m-%d %H:%M:%S");
+        match event.change {
+            Change::Created(task) => println!("{}  {}  {}{}", at, t("history_created"), task.description, due_label(&task)),
+            Change::Edited(task) => println!("{}  {}  {}{}", at, t("history_edited"), task.description, due_label(&task)),
+            Change::Completed(_) => println!("{}  {}", at, t("history_completed")),
+            Change::Deleted(_) => println!("{}  {}", at, t("history_deleted")),
+            Change::Pomodoro(_) => println!("{}  {}", at, t("history_pomodoro")),
+        }
+    }
+}
+
+
+pub fn ask_task_to_focus() -> u32{
+
+    println!("\n{}\n", t("ask_focus"));
+
+    loop {
+        let mut task_to_focus = String::new();
+        io::stdin().read_line(&mut task_to_focus).expect("Failed to read line");
+
+        match task_to_focus.trim().parse::<u32>() {
             Ok(id) => return id,
-            Err(_) => println!("\nType the id of the task you want to delete.\n")
+            Err(_) => println!("\n{}\n", t("retry_focus"))
         }
     }
 }
 
 
+pub fn focus_start(task: &Task) {
+
+    println!("\n{}", t("focus_start").replace("{id}", &task.id.to_string()).replace("{description}", &task.description));
+}
+
+
+/// Counts down `seconds` on a single terminal line.
+pub fn countdown(cycle: &str, key: &'static str, seconds: u64) {
+
+    for remaining in (1..=seconds).rev() {
+        print!("\r[{}] {} {:02}:{:02} ", cycle, t(key), remaining / 60, remaining % 60);
+        io::stdout().flush().expect("Failed to flush");
+        thread::sleep(Duration::from_secs(1));
+    }
+    print!("\r[{}] {} 00:00 ", cycle, t(key));
+    println!();
+}
+
+
+pub fn display_stats(tasks: Vec<Task>, now: NaiveDateTime) {
+
+    let completed = tasks.iter().filter(|task| task.completed).count();
+    let overdue = tasks.iter().filter(|task| !task.completed && task.due.is_some_and(|due| due < now)).count();
+    let pomodoros: u32 = tasks.iter().map(|task| task.pomodoros).sum();
+
+    println!("\n{}", t("stats_header"));
+    println!("{}{}", t("stats_tasks"), tasks.len());
+    println!("{}{}", t("stats_completed"), completed);
+    println!("{}{}", t("stats_pending"), tasks.len() - completed);
+    println!("{}{}", t("stats_overdue"), overdue);
+    println!("{}{}", t("stats_pomodoros"), pomodoros);
+
+    if let Some(task) = tasks.iter().filter(|task| task.pomodoros > 0).max_by_key(|task| task.pomodoros) {
+        println!("{}[{}] {} ({})", t("stats_most_focused"), task.id, task.description, t("pomodoros").replace("{count}", &task.pomodoros.to_string()));
+    }
+}
+
+
+pub fn ask_passphrase() -> String{
+
+    rpassword::prompt_password(format!("\n{}", t("ask_passphrase"))).expect("Failed to read passphrase")
+}
+
+
+pub fn ask_new_passphrase() -> String{
+
+    loop {
+        let passphrase = rpassword::prompt_password(format!("\n{}", t("ask_new_passphrase"))).expect("Failed to read passphrase");
+        let confirmation = rpassword::prompt_password(t("confirm_passphrase")).expect("Failed to read passphrase");
+
+        if passphrase.is_empty() {
+            println!("\n{}", t("empty_passphrase"));
+        } else if passphrase != confirmation {
+            println!("\n{}", t("passphrase_mismatch"));
+        } else {
+            return passphrase;
+        }
+    }
+}
+
+
+pub fn display_templates(templates: Vec<Template>) {
+
+    if templates.is_empty() {
+        println!("\n{}", t("no_templates"));
+        return;
+    }
+
+    println!("\n{}", t("templates_header"));
+
+    for template in templates {
+        println!("\n{}", template.name);
+        for description in &template.tasks {
+            println!("  - {}", description);
+        }
+    }
+}
+
+
+pub fn ask_placeholder(name: &str) -> String {
+
+    println!("\n{}", t("ask_placeholder").replace("{name}", name));
+
+    let mut value = String::new();
+    io::stdin().read_line(&mut value).expect("Failed to read line");
+
+    value.trim().to_string()
+}
+
+
 pub fn add_success() {
-    println!("\nTask added !");
+    println!("\n{}", t("add_success"));
 }
 
 pub fn edit_success() {
-    println!("\nTask edited !");
+    println!("\n{}", t("edit_success"));
+}
+
+pub fn mark_success(count: usize) {
+    if count == 1 {println!("\n{}", t("mark_success"))} else {println!("\n{}", t("mark_success_many").replace("{count}", &count.to_string()))};
+}
+
+pub fn delete_success(count: usize) {
+    if count == 1 {println!("\n{}", t("delete_success"))} else {println!("\n{}", t("delete_success_many").replace("{count}", &count.to_string()))};
+}
+
+pub fn cancelled() {
+    println!("\n{}", t("cancelled"));
+}
+
+pub fn notes_success() {
+    println!("\n{}", t("notes_success"));
+}
+
+pub fn attach_success() {
+    println!("\n{}", t("attach_success"));
+}
+
+pub fn compact_success(events: usize) {
+    println!("\n{}", t("compact_success").replace("{count}", &events.to_string()));
+}
+
+pub fn encrypt_success() {
+    println!("\n{}", t("encrypt_success"));
+}
+
+pub fn decrypt_success() {
+    println!("\n{}", t("decrypt_success"));
+}
+
+pub fn already_encrypted() {
+    println!("\n{}", t("already_encrypted"));
+}
+
+pub fn not_encrypted() {
+    println!("\n{}", t("not_encrypted"));
+}
+
+pub fn pomodoro_done(count: u32) {
+    println!("{}", t("pomodoro_done").replace("{count}", &count.to_string()));
+}
+
+pub fn focus_success() {
+    println!("\n{}", t("focus_success"));
+}
+
+pub fn template_saved(name: &str) {
+    println!("\n{}", t("template_saved").replace("{name}", name));
+}
+
+pub fn template_removed(name: &str) {
+    println!("\n{}", t("template_removed").replace("{name}", name));
+}
+
+pub fn template_not_found(name: &str) {
+    println!("\n{}", t("template_not_found").replace("{name}", name));
+}
+
+pub fn template_success(count: usize, name: &str) {
+    println!("\n{}", t("template_success").replace("{count}", &count.to_string()).replace("{name}", name));
+}
+
+pub fn invalid_value(value: &str) {
+    println!("\n{}", t("invalid_value").replace("{value}", value));
 }
 
-pub fn mark_success() {
-    println!("\nTask marked !");
+pub fn vetoed(error: String) {
+    println!("\n{} {}", t("vetoed"), error);
 }
 
-pub fn delete_success() {
-    println!("\nTask deleted !");
+pub fn invalid_selection(error: String) {
+    println!("\n{}", error);
 }
 
 pub fn not_found() {
-    println!("\nTask not found !")
+    println!("\n{}", t("not_found"))
 }
\ No newline at end of file
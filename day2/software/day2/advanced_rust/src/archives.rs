@@ -0,0 +1,458 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::listing::{Entry, EntryKind, relative_name};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+}
+
+/// An entry of an archive, as listed without extracting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path relative to the root of the archive.
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Target of a symlink.
+    pub link: Option<PathBuf>,
+}
+
+
+impl ArchiveFormat {
+    /// Format matching the extension of `path`: `.tar` or `.zip`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "tar" => Some(ArchiveFormat::Tar),
+            "zip" => Some(ArchiveFormat::Zip),
+            _ => None,
+        }
+    }
+}
+
+
+impl FileManager {
+    /// Archives the content of the directory `src`, its entries being stored relative to it.
+    pub fn create_archive(src: impl AsRef<Path>, archive: impl AsRef<Path>, format: ArchiveFormat) -> Result<(), Errors> {
+
+        let (src, archive) = (src.as_ref(), archive.as_ref());
+        check_path(Operation::Archive, src)?;
+        check_path(Operation::Archive, archive)?;
+
+        if !src.is_dir() {
+            return Err(Errors::new(ErrorKind::NotADirectory, Operation::Archive, &[src, archive]));
+        }
+
+        let entries = FileManager::list(src).recursive().hidden(true).into_iter().collect::<Result<Vec<Entry>, Errors>>()?;
+        let file = File::create(archive).map_err(|e| Errors::io(Operation::Archive, &[archive], e))?;
+        let writer = BufWriter::new(file);
+
+        let written = match format {
+            ArchiveFormat::Tar => write_tar(writer, src, &entries),
+            ArchiveFormat::Zip => write_zip(writer, src, &entries),
+        };
+
+        written.map_err(|e| Errors::io(Operation::Archive, &[src, archive], e))
+    }
+
+
+    /// Extracts an archive into `dest`, created if needed. Nothing is extracted if one of the entries,
+    /// or the target of one of its symlinks, has an absolute path or a `..` component: the archive is
+    /// rejected with `InvalidPath`.
+    pub fn extract_archive(archive: impl AsRef<Path>, dest: impl AsRef<Path>, format: ArchiveFormat) -> Result<(), Errors> {
+
+        let (archive, dest) = (archive.as_ref(), dest.as_ref());
+        check_path(Operation::Extract, dest)?;
+
+        for entry in Self::list_archive(archive, format)? {
+            if enclosed(&entry.path).is_none() || entry.link.as_deref().is_some_and(|link| enclosed(link).is_none()) {
+                return Err(Errors::new(ErrorKind::InvalidPath, Operation::Extract, &[archive, &dest.join(&entry.path)]));
+            }
+        }
+
+        fs::create_dir_all(dest).map_err(|e| Errors::io(Operation::Extract, &[dest], e))?;
+        let file = File::open(archive).map_err(|e| Errors::io(Operation::Extract, &[archive], e))?;
+
+        let extracted = match format {
+            ArchiveFormat::Tar => extract_tar(BufReader::new(file), dest),
+            ArchiveFormat::Zip => extract_zip(BufReader::new(file), dest),
+        };
+
+        extracted.map_err(|e| Errors::io(Operation::Extract, &[archive, dest], e))
+    }
+
+
+    pub fn list_archive(archive: impl AsRef<Path>, format: ArchiveFormat) -> Result<Vec<ArchiveEntry>, Errors> {
+
+        let archive = archive.as_ref();
+        check_path(Operation::Extract, archive)?;
+
+        let file = File::open(archive).map_err(|e| Errors::io(Operation::Extract, &[archive], e))?;
+
+        let listed = match format {
+            ArchiveFormat::Tar => list_tar(BufReader::new(file)),
+            ArchiveFormat::Zip => list_zip(BufReader::new(file)),
+        };
+
+        listed.map_err(|e| Errors::io(Operation::Extract, &[archive], e))
+    }
+}
+
+
+/// The path an entry is extracted to under the destination, or None if it would land outside of it.
+fn enclosed(path: &Path) -> Option<PathBuf> {
+
+    let mut enclosed = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => enclosed.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(enclosed)
+}
+
+
+fn write_tar(writer: BufWriter<File>, src: &Path, entries: &[Entry]) -> io::Result<()> {
+
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+
+    for entry in entries {
+        let name = relative_name(src, &entry.path);
+        match entry.kind {
+            EntryKind::Directory => builder.append_dir(&name, &entry.path)?,
+            EntryKind::File | EntryKind::Symlink => builder.append_path_with_name(&entry.path, &name)?,
+            // Sockets, fifos and devices are not archived.
+            EntryKind::Other => {}
+        }
+    }
+
+    builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+    Ok(())
+}
+
+
+fn write_zip(writer: BufWriter<File>, src: &Path, entries: &[Entry]) -> io::Result<()> {
+
+    let mut zip = ZipWriter::new(writer);
+
+    for entry in entries {
+        let name = relative_name(src, &entry.path);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated).unix_permissions(entry.mode);
+        match entry.kind {
+            EntryKind::Directory => zip.add_directory(name, options)?,
+            EntryKind::File => {
+                zip.start_file(name, options)?;
+                io::copy(&mut File::open(&entry.path)?, &mut zip)?;
+            }
+            EntryKind::Symlink => zip.add_symlink_from_path(name, fs::read_link(&entry.path)?, options)?,
+            EntryKind::Other => {}
+        }
+    }
+
+    zip.finish()?.into_inner().map_err(|e| e.into_error())?;
+    Ok(())
+}
+
+
+fn list_tar(reader: BufReader<File>) -> io::Result<Vec<ArchiveEntry>> {
+
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let kind = match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+            tar::EntryType::Directory => EntryKind::Directory,
+            tar::EntryType::Symlink | tar::EntryType::Link => EntryKind::Symlink,
+            _ => EntryKind::Other,
+        };
+        let link = entry.link_name()?.map(|link| link.into_owned());
+        entries.push(ArchiveEntry { path: entry.path()?.into_owned(), kind, size: entry.size(), link });
+    }
+
+    Ok(entries)
+}
+
+
+fn list_zip(reader: BufReader<File>) -> io::Result<Vec<ArchiveEntry>> {
+
+    let mut archive = ZipArchive::new(reader)?;
+    let mut entries = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let (kind, link) = if file.is_dir() {
+            (EntryKind::Directory, None)
+        } else if file.is_symlink() {
+            let mut link = String::new();
+            file.read_to_string(&mut link)?;
+            (EntryKind::Symlink, Some(PathBuf::from(link)))
+        } else {
+            (EntryKind::File, None)
+        };
+        entries.push(ArchiveEntry { path: PathBuf::from(file.name()), kind, size: file.size(), link });
+    }
+
+    Ok(entries)
+}
+
+
+fn extract_tar(reader: BufReader<File>, dest: &Path) -> io::Result<()> {
+
+    // Permissions are kept without their setuid, setgid and sticky bits, which an untrusted archive must not set.
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_mtime(true);
+
+    // unpack_in skips entries that would escape `dest`, which were already rejected anyway.
+    for entry in archive.entries()? {
+        entry?.unpack_in(dest)?;
+    }
+    Ok(())
+}
+
+
+fn extract_zip(reader: BufReader<File>, dest: &Path) -> io::Result<()> {
+
+    let mut archive = ZipArchive::new(reader)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let Some(relative) = enclosed(Path::new(file.name())) else {
+            continue;
+        };
+        let target = dest.join(relative);
+
+        if file.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if file.is_symlink() {
+            let mut link = String::new();
+            file.read_to_string(&mut link)?;
+            symlink(Path::new(&link), &target)?;
+            continue;
+        }
+
+        io::copy(&mut file, &mut File::create(&target)?)?;
+        set_mode(&target, file.unix_mode())?;
+    }
+    Ok(())
+}
+
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) -> io::Result<()> {
+
+    use std::os::unix::fs::PermissionsExt;
+
+    match mode {
+        // Without the setuid, setgid and sticky bits, as for tar archives.
+        Some(mode) => fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777)),
+        None => Ok(()),
+    }
+}
+
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: Option<u32>) -> io::Result<()> {
+
+    Ok(())
+}
+
+
+#[cfg(unix)]
+fn symlink(link: &Path, target: &Path) -> io::Result<()> {
+
+    std::os::unix::fs::symlink(link, target)
+}
+
+
+/// Without unix symlinks, links are not extracted.
+#[cfg(not(unix))]
+fn symlink(_link: &Path, _target: &Path) -> io::Result<()> {
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A tar holding one entry, its name written as is so that it can escape.
+    fn raw_tar(dir: &TempDir, name: &[u8], entry_type: tar::EntryType, link: Option<&str>) -> PathBuf {
+        let archive = dir.path().join("malicious.tar");
+        let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name);
+        header.set_entry_type(entry_type);
+        header.set_mode(0o644);
+        header.set_size(if link.is_some() { 0 } else { 7 });
+        if let Some(link) = link {
+            header.set_link_name(link).unwrap();
+        }
+        header.set_cksum();
+
+        let data: &[u8] = if link.is_some() { b"" } else { b"escaped" };
+        builder.append(&header, data).unwrap();
+        builder.into_inner().unwrap();
+        archive
+    }
+
+    fn raw_zip(dir: &TempDir, name: &str, link: Option<&str>) -> PathBuf {
+        let archive = dir.path().join("malicious.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+
+        match link {
+            Some(link) => zip.add_symlink(name, link, SimpleFileOptions::default()).unwrap(),
+            None => {
+                zip.start_file(name, SimpleFileOptions::default()).unwrap();
+                io::Write::write_all(&mut zip, b"escaped").unwrap();
+            }
+        }
+        zip.finish().unwrap();
+        archive
+    }
+
+    fn assert_rejected(dir: &TempDir, archive: &Path, format: ArchiveFormat) {
+        let dest = dir.path().join("dest");
+        let error = FileManager::extract_archive(archive, &dest, format).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidPath);
+        assert!(!dest.exists(), "nothing is extracted from a rejected archive");
+        assert!(!dir.path().join("escaped.txt").exists());
+    }
+
+    #[test]
+    fn round_trips_both_formats() {
+        for format in [ArchiveFormat::Tar, ArchiveFormat::Zip] {
+            let dir = tempfile::tempdir().unwrap();
+            let src = dir.path().join("src");
+            fs::create_dir_all(src.join("nested/empty")).unwrap();
+            fs::write(src.join("top.txt"), "top").unwrap();
+            fs::write(src.join("nested/deep.txt"), "deep").unwrap();
+            #[cfg(unix)]
+            std::os::unix::fs::symlink("nested/deep.txt", src.join("link")).unwrap();
+
+            let archive = dir.path().join("archive");
+            FileManager::create_archive(&src, &archive, format).unwrap();
+
+            let mut listed: Vec<(String, EntryKind, u64)> = FileManager::list_archive(&archive, format).unwrap()
+                .into_iter()
+                .map(|entry| (entry.path.to_string_lossy().trim_end_matches('/').to_string(), entry.kind, entry.size))
+                .filter(|(_, kind, _)| *kind != EntryKind::Symlink)
+                .collect();
+            listed.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(listed, [
+                ("nested".to_string(), EntryKind::Directory, 0),
+                ("nested/deep.txt".to_string(), EntryKind::File, 4),
+                ("nested/empty".to_string(), EntryKind::Directory, 0),
+                ("top.txt".to_string(), EntryKind::File, 3),
+            ], "{:?}", format);
+
+            let dest = dir.path().join("dest");
+            FileManager::extract_archive(&archive, &dest, format).unwrap();
+            assert_eq!(fs::read_to_string(dest.join("top.txt")).unwrap(), "top");
+            assert_eq!(fs::read_to_string(dest.join("nested/deep.txt")).unwrap(), "deep");
+            assert!(dest.join("nested/empty").is_dir());
+            #[cfg(unix)]
+            assert_eq!(fs::read_link(dest.join("link")).unwrap(), Path::new("nested/deep.txt"));
+        }
+    }
+
+    #[test]
+    fn rejects_tar_entries_going_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = raw_tar(&dir, b"../escaped.txt", tar::EntryType::Regular, None);
+
+        assert_rejected(&dir, &archive, ArchiveFormat::Tar);
+    }
+
+    #[test]
+    fn rejects_absolute_tar_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().join("escaped.txt");
+        let archive = raw_tar(&dir, absolute.to_string_lossy().as_bytes(), tar::EntryType::Regular, None);
+
+        assert_rejected(&dir, &archive, ArchiveFormat::Tar);
+    }
+
+    #[test]
+    fn rejects_tar_symlinks_leading_outside() {
+        for target in ["../escaped.txt", "/etc/passwd"] {
+            let dir = tempfile::tempdir().unwrap();
+            let archive = raw_tar(&dir, b"link", tar::EntryType::Symlink, Some(target));
+
+            assert_rejected(&dir, &archive, ArchiveFormat::Tar);
+        }
+    }
+
+    #[test]
+    fn rejects_zip_entries_going_up_or_absolute() {
+        for name in ["../escaped.txt", "/escaped.txt"] {
+            let dir = tempfile::tempdir().unwrap();
+            let archive = raw_zip(&dir, name, None);
+
+            assert_rejected(&dir, &archive, ArchiveFormat::Zip);
+        }
+    }
+
+    #[test]
+    fn rejects_zip_symlinks_leading_outside() {
+        for target in ["../escaped.txt", "/etc/passwd"] {
+            let dir = tempfile::tempdir().unwrap();
+            let archive = raw_zip(&dir, "link", Some(target));
+
+            assert_rejected(&dir, &archive, ArchiveFormat::Zip);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_extract_setuid_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let tar = dir.path().join("setuid.tar");
+        let mut builder = tar::Builder::new(File::create(&tar).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o4755);
+        header.set_cksum();
+        builder.append_data(&mut header, "tool", &b"tool"[..]).unwrap();
+        builder.into_inner().unwrap();
+
+        let zip = dir.path().join("setuid.zip");
+        let mut writer = ZipWriter::new(File::create(&zip).unwrap());
+        writer.start_file("tool", SimpleFileOptions::default().unix_permissions(0o6755)).unwrap();
+        io::Write::write_all(&mut writer, b"tool").unwrap();
+        writer.finish().unwrap();
+
+        for (archive, format) in [(tar, ArchiveFormat::Tar), (zip, ArchiveFormat::Zip)] {
+            let dest = dir.path().join(format!("{:?}", format));
+            FileManager::extract_archive(&archive, &dest, format).unwrap();
+
+            let mode = fs::metadata(dest.join("tool")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o7000, 0, "{:?}", format);
+            assert_eq!(mode & 0o100, 0o100, "{:?}", format);
+        }
+    }
+}
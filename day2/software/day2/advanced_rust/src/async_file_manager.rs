@@ -0,0 +1,109 @@
+use std::path::Path;
+use tokio::fs;
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::check_path;
+
+/// Counterpart of `FileManager` for async code, running the file system calls on tokio's blocking pool.
+pub struct AsyncFileManager;
+
+
+impl AsyncFileManager {
+    pub async fn list_files(path: impl AsRef<Path>) -> Result<Vec<String>, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::List, path)?;
+
+        let mut list = fs::read_dir(path).await.map_err(|e| Errors::io(Operation::List, &[path], e))?;
+
+        let mut files = Vec::new();
+        while let Some(element) = list.next_entry().await.map_err(|e| Errors::io(Operation::List, &[path], e))? {
+            let is_file = element.file_type().await.map_err(|e| Errors::io(Operation::List, &[&element.path()], e))?;
+            if is_file.is_file() {
+                files.push(element.file_name().to_string_lossy().into_owned());
+            }
+        }
+        Ok(files)
+    }
+
+
+    pub async fn read_file(path: impl AsRef<Path>) -> Result<String, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        fs::read_to_string(path).await.map_err(|e| Errors::io(Operation::Read, &[path], e))
+    }
+
+
+    pub async fn write_file(path: impl AsRef<Path>, content: &str) -> Result<(), Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Write, path)?;
+
+        fs::write(path, content).await.map_err(|e| Errors::io(Operation::Write, &[path], e))
+    }
+
+
+    pub async fn copy_file(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<(), Errors> {
+
+        let (src, dest) = (src.as_ref(), dest.as_ref());
+        check_path(Operation::Copy, src)?;
+        check_path(Operation::Copy, dest)?;
+
+        if fs::metadata(src).await.is_ok_and(|metadata| metadata.is_dir()) {
+            return Err(Errors::new(ErrorKind::IsADirectory, Operation::Copy, &[src, dest]));
+        }
+
+        fs::copy(src, dest).await.map(|_| ()).map_err(|e| Errors::io(Operation::Copy, &[src, dest], e))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn writes_reads_and_copies() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.txt");
+        let destination = dir.path().join("destination.txt");
+
+        AsyncFileManager::write_file(&source, "Hello, World!").await.unwrap();
+        AsyncFileManager::copy_file(&source, &destination).await.unwrap();
+
+        assert_eq!(AsyncFileManager::read_file(&destination).await.unwrap(), "Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn lists_only_files() {
+        let dir = tempdir().unwrap();
+        AsyncFileManager::write_file(dir.path().join("a.txt"), "a").await.unwrap();
+        AsyncFileManager::write_file(dir.path().join("b.txt"), "b").await.unwrap();
+        fs::create_dir(dir.path().join("nested")).await.unwrap();
+
+        let mut files = AsyncFileManager::list_files(dir.path()).await.unwrap();
+        files.sort();
+
+        assert_eq!(files, ["a.txt", "b.txt"]);
+    }
+
+    #[tokio::test]
+    async fn maps_errors_with_context() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("missing.txt");
+        let file = dir.path().join("file.txt");
+        AsyncFileManager::write_file(&file, "").await.unwrap();
+
+        let error = AsyncFileManager::read_file(&missing).await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::FileNotFound);
+        assert_eq!(error.operation(), Some(Operation::Read));
+        assert_eq!(error.paths(), [missing]);
+
+        assert_eq!(AsyncFileManager::list_files(&file).await.unwrap_err().kind(), ErrorKind::NotADirectory);
+        assert_eq!(AsyncFileManager::read_file(dir.path()).await.unwrap_err().kind(), ErrorKind::IsADirectory);
+        assert_eq!(AsyncFileManager::copy_file(dir.path(), &file).await.unwrap_err().kind(), ErrorKind::IsADirectory);
+        assert_eq!(AsyncFileManager::write_file("", "").await.unwrap_err().kind(), ErrorKind::InvalidPath);
+    }
+}
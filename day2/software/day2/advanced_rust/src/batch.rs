@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+use crate::checksums::Algorithm;
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem};
+use crate::listing::EntryKind;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    Copy { src: PathBuf, dest: PathBuf },
+    /// Deletes a file, or a directory with everything under it.
+    Delete(PathBuf),
+    Hash(PathBuf, Algorithm),
+}
+
+/// What a successful batch operation gives back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutput {
+    Copied,
+    Deleted,
+    Hashed(String),
+}
+
+/// Operations to run in parallel, configured with its builder methods and started with `run`.
+#[must_use = "a batch does nothing until `run` is called"]
+pub struct Batch<'a, F: FileSystem + Sync = OsFileSystem> {
+    fs: &'a F,
+    operations: Vec<BatchOperation>,
+    threads: usize,
+}
+
+
+impl FileManager {
+    /// Copies, deletes or hashes many paths at once on a thread pool, e.g. thousands of small files.
+    pub fn batch(operations: impl IntoIterator<Item = BatchOperation>) -> Batch<'static> {
+
+        Self::batch_on(&OsFileSystem, operations)
+    }
+
+
+    /// Same as `batch`, on the given file system, which all the threads share.
+    pub fn batch_on<F: FileSystem + Sync>(fs: &F, operations: impl IntoIterator<Item = BatchOperation>) -> Batch<'_, F> {
+
+        Batch { fs, operations: operations.into_iter().collect(), threads: 0 }
+    }
+}
+
+
+impl<F: FileSystem + Sync> Batch<'_, F> {
+    /// Number of threads to run the operations on, one per CPU by default.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+
+    /// Runs every operation, returning their results in the order of the operations. A failed operation
+    /// does not stop the others; the batch itself only fails if its threads cannot be started.
+    pub fn run(self) -> Result<Vec<Result<BatchOutput, Errors>>, Errors> {
+
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+            .map_err(|e| Errors::from(std::io::Error::other(e)))?;
+
+        Ok(pool.install(|| self.operations.par_iter().map(|operation| execute(self.fs, operation)).collect()))
+    }
+}
+
+
+fn execute(fs: &impl FileSystem, operation: &BatchOperation) -> Result<BatchOutput, Errors> {
+
+    match operation {
+        BatchOperation::Copy { src, dest } => FileManager::copy_file_on(fs, src, dest).map(|_| BatchOutput::Copied),
+        BatchOperation::Delete(path) => delete(fs, path).map(|_| BatchOutput::Deleted),
+        BatchOperation::Hash(path, algorithm) => FileManager::checksum_on(fs, path, *algorithm).map(BatchOutput::Hashed),
+    }
+}
+
+
+fn delete(fs: &impl FileSystem, path: &Path) -> Result<(), Errors> {
+
+    check_path(Operation::Delete, path)?;
+
+    let metadata = fs.symlink_metadata(path).map_err(|e| Errors::io(Operation::Delete, &[path], e))?;
+    let removed = if metadata.kind == EntryKind::Directory { fs.remove_dir_all(path) } else { fs.remove_file(path) };
+
+    removed.map_err(|e| Errors::io(Operation::Delete, &[path], e))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn runs_every_operation_in_order_despite_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 0..20 {
+            fs::write(dir.path().join(format!("{}.txt", index)), index.to_string()).unwrap();
+        }
+        fs::create_dir_all(dir.path().join("tree/sub")).unwrap();
+        fs::write(dir.path().join("tree/sub/file.txt"), "").unwrap();
+
+        let mut operations = vec![
+            BatchOperation::Copy { src: dir.path().join("missing.txt"), dest: dir.path().join("copy.txt") },
+            BatchOperation::Delete(dir.path().join("tree")),
+            BatchOperation::Copy { src: dir.path().join("0.txt"), dest: dir.path().join("copy.txt") },
+        ];
+        operations.extend((0..20).map(|index| BatchOperation::Hash(dir.path().join(format!("{}.txt", index)), Algorithm::Xxh3)));
+
+        let results = FileManager::batch(operations).threads(4).run().unwrap();
+
+        assert_eq!(results.len(), 23);
+        assert_eq!(results[0].as_ref().unwrap_err().kind(), ErrorKind::FileNotFound);
+        assert_eq!(results[1].as_ref().ok(), Some(&BatchOutput::Deleted));
+        assert_eq!(results[2].as_ref().ok(), Some(&BatchOutput::Copied));
+        for (index, result) in results[3..].iter().enumerate() {
+            let expected = FileManager::checksum(dir.path().join(format!("{}.txt", index)), Algorithm::Xxh3).unwrap();
+            assert_eq!(result.as_ref().ok(), Some(&BatchOutput::Hashed(expected)));
+        }
+
+        assert!(!dir.path().join("tree").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("copy.txt")).unwrap(), "0");
+    }
+
+    #[test]
+    fn runs_an_empty_batch() {
+        assert!(FileManager::batch([]).run().unwrap().is_empty());
+    }
+
+    #[test]
+    fn runs_on_the_given_file_system() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("root/sub")).unwrap();
+        fs::write(dir.path().join("root/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+        let rooted = FileManager::rooted(dir.path().join("root")).unwrap();
+
+        let results = FileManager::batch_on(&rooted, [
+            BatchOperation::Copy { src: "a.txt".into(), dest: "sub/b.txt".into() },
+            BatchOperation::Copy { src: "../secret.txt".into(), dest: "stolen.txt".into() },
+            BatchOperation::Hash("a.txt".into(), Algorithm::Sha256),
+            BatchOperation::Delete(".".into()),
+            BatchOperation::Delete("../secret.txt".into()),
+        ]).run().unwrap();
+
+        assert_eq!(results[0].as_ref().ok(), Some(&BatchOutput::Copied));
+        assert_eq!(results[1].as_ref().unwrap_err().kind(), ErrorKind::InvalidPath);
+        assert_eq!(results[2].as_ref().ok(), Some(&BatchOutput::Hashed(FileManager::checksum(dir.path().join("root/a.txt"), Algorithm::Sha256).unwrap())));
+        assert_eq!(results[3].as_ref().unwrap_err().kind(), ErrorKind::InvalidPath);
+        assert_eq!(results[4].as_ref().unwrap_err().kind(), ErrorKind::InvalidPath);
+
+        assert_eq!(fs::read_to_string(dir.path().join("root/sub/b.txt")).unwrap(), "a");
+        assert!(!dir.path().join("root/stolen.txt").exists());
+        assert!(dir.path().join("secret.txt").exists());
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use xxhash_rust::xxh3::Xxh3;
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{CHUNK_SIZE, FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem};
+use crate::listing::EntryKind;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Cryptographic, for digests published or checked against untrusted sources.
+    Sha256,
+    /// XXH3 64 bits, much faster, to compare files with each other.
+    Xxh3,
+}
+
+/// Files with the same size and digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub digest: String,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Duplicate files found under a directory, the groups wasting the most space first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Duplicates {
+    pub groups: Vec<DuplicateGroup>,
+    /// Bytes that would be freed by keeping a single file of each group.
+    pub wasted: u64,
+}
+
+
+impl FileManager {
+    /// Digest of the file as lowercase hexadecimal.
+    pub fn checksum(path: impl AsRef<Path>, algorithm: Algorithm) -> Result<String, Errors> {
+
+        Self::checksum_on(&OsFileSystem, path, algorithm)
+    }
+
+
+    /// Checks the file against a hexadecimal digest, whatever its case.
+    pub fn verify(path: impl AsRef<Path>, algorithm: Algorithm, expected: &str) -> Result<bool, Errors> {
+
+        Self::verify_on(&OsFileSystem, path, algorithm, expected)
+    }
+
+
+    /// Finds the files under `path` with the same content. Only files of the same size are hashed,
+    /// and empty files are ignored.
+    pub fn find_duplicates(path: impl AsRef<Path>, algorithm: Algorithm) -> Result<Duplicates, Errors> {
+
+        Self::find_duplicates_on(&OsFileSystem, path, algorithm)
+    }
+
+
+    /// Same as `checksum`, on the given file system.
+    pub fn checksum_on(fs: &impl FileSystem, path: impl AsRef<Path>, algorithm: Algorithm) -> Result<String, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Hash, path)?;
+
+        let mut file = fs.open(path).map_err(|e| Errors::io(Operation::Hash, &[path], e))?;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut hasher = Hasher::new(algorithm);
+
+        loop {
+            let read = file.read(&mut buffer).map_err(|e| Errors::io(Operation::Hash, &[path], e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        Ok(hasher.finish())
+    }
+
+
+    /// Same as `verify`, on the given file system.
+    pub fn verify_on(fs: &impl FileSystem, path: impl AsRef<Path>, algorithm: Algorithm, expected: &str) -> Result<bool, Errors> {
+
+        Ok(Self::checksum_on(fs, path, algorithm)?.eq_ignore_ascii_case(expected.trim()))
+    }
+
+
+    /// Same as `find_duplicates`, on the given file system.
+    pub fn find_duplicates_on(fs: &impl FileSystem, path: impl AsRef<Path>, algorithm: Algorithm) -> Result<Duplicates, Errors> {
+
+        let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+        for entry in FileManager::list_on(fs, path).recursive().hidden(true).filter(|entry| entry.kind == EntryKind::File && entry.size > 0) {
+            let entry = entry?;
+            by_size.entry(entry.size).or_default().push(entry.path);
+        }
+
+        let mut groups = Vec::new();
+        for (size, paths) in by_size.into_iter().filter(|(_, paths)| paths.len() > 1) {
+            let mut by_digest: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+            for path in paths {
+                by_digest.entry(Self::checksum_on(fs, &path, algorithm)?).or_default().push(path);
+            }
+            groups.extend(
+                by_digest.into_iter()
+                    .filter(|(_, paths)| paths.len() > 1)
+                    .map(|(digest, paths)| DuplicateGroup { size, digest, paths }),
+            );
+        }
+
+        groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.paths.cmp(&b.paths)));
+        let wasted = groups.iter().map(DuplicateGroup::wasted).sum();
+
+        Ok(Duplicates { groups, wasted })
+    }
+}
+
+
+impl DuplicateGroup {
+    /// Bytes taken by all the copies but one.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+
+enum Hasher {
+    Sha256(Sha256),
+    Xxh3(Box<Xxh3>),
+}
+
+
+impl Hasher {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::Xxh3 => Hasher::Xxh3(Box::new(Xxh3::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Xxh3(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Hasher::Sha256(hasher) => hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect(),
+            Hasher::Xxh3(hasher) => format!("{:016x}", hasher.digest()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::errors::ErrorKind;
+    use crate::file_system::MemoryFileSystem;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn hashes_and_verifies_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(FileManager::checksum(&path, Algorithm::Sha256).unwrap(), ABC_SHA256);
+        assert_eq!(FileManager::checksum(&path, Algorithm::Xxh3).unwrap().len(), 16);
+        assert!(FileManager::verify(&path, Algorithm::Sha256, &format!(" {} ", ABC_SHA256.to_uppercase())).unwrap());
+        assert!(!FileManager::verify(&path, Algorithm::Sha256, "00").unwrap());
+    }
+
+    #[test]
+    fn groups_duplicates_and_totals_the_wasted_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("a.txt", "twelve bytes"), ("sub/b.txt", "twelve bytes"), ("sub/.c.txt", "twelve bytes"),
+            ("d.txt", "four"), ("sub/e.txt", "four"),
+            ("same_size.txt", "12 bytes too"), ("unique.txt", "unique"), ("empty1", ""), ("empty2", ""),
+        ];
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let duplicates = FileManager::find_duplicates(dir.path(), Algorithm::Xxh3).unwrap();
+
+        let groups: Vec<(u64, Vec<PathBuf>)> = duplicates.groups.iter()
+            .map(|group| {
+                let mut paths = group.paths.clone();
+                paths.sort();
+                (group.size, paths)
+            })
+            .collect();
+        assert_eq!(groups, [
+            (12, vec![dir.path().join("a.txt"), dir.path().join("sub/.c.txt"), dir.path().join("sub/b.txt")]),
+            (4, vec![dir.path().join("d.txt"), dir.path().join("sub/e.txt")]),
+        ]);
+        assert_eq!(duplicates.groups[0].wasted(), 24);
+        assert_eq!(duplicates.wasted, 24 + 4);
+    }
+
+    #[test]
+    fn finds_nothing_without_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        fs::write(dir.path().join("b.txt"), "b").unwrap();
+
+        assert_eq!(FileManager::find_duplicates(dir.path(), Algorithm::Sha256).unwrap(), Duplicates::default());
+    }
+
+    #[test]
+    fn works_on_any_file_system() {
+        let fs = MemoryFileSystem::new()
+            .with_file("/a.txt", "abc")
+            .with_file("/sub/b.txt", "abc")
+            .with_file("/sub/c.txt", "abd");
+
+        assert_eq!(FileManager::checksum_on(&fs, "/a.txt", Algorithm::Sha256).unwrap(), ABC_SHA256);
+        assert!(FileManager::verify_on(&fs, "/sub/b.txt", Algorithm::Sha256, ABC_SHA256).unwrap());
+        assert_eq!(FileManager::checksum_on(&fs, "/missing.txt", Algorithm::Xxh3).unwrap_err().kind(), ErrorKind::FileNotFound);
+
+        let duplicates = FileManager::find_duplicates_on(&fs, "/", Algorithm::Xxh3).unwrap();
+        assert_eq!(duplicates.groups.len(), 1);
+        assert_eq!(duplicates.groups[0].paths, [PathBuf::from("/a.txt"), PathBuf::from("/sub/b.txt")]);
+        assert_eq!(duplicates.wasted, 3);
+    }
+}
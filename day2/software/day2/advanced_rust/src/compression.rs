@@ -0,0 +1,254 @@
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use flate2::Compression as GzipLevel;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem};
+use crate::reading::Lines;
+
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression level of the zstd files written, zstd's own default.
+const ZSTD_LEVEL: i32 = 3;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+
+impl Compression {
+    /// Compression matching the extension of `path`: `.gz`, `.zst` or `.zstd`.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("gz") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Compression whose magic bytes start `header`.
+    pub fn from_magic(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+
+impl FileManager {
+    /// Opens a file for reading, decompressing it if its first bytes are those of a gzip or zstd stream.
+    pub fn open_decompressed(path: impl AsRef<Path>) -> Result<Box<dyn BufRead>, Errors> {
+
+        Self::open_decompressed_on(&OsFileSystem, path)
+    }
+
+
+    /// Reads a file, decompressing it if needed.
+    pub fn read_decompressed(path: impl AsRef<Path>) -> Result<Vec<u8>, Errors> {
+
+        Self::read_decompressed_on(&OsFileSystem, path)
+    }
+
+
+    /// Lines of a file, decompressed on the fly if needed.
+    pub fn decompressed_lines(path: impl AsRef<Path>) -> Result<Lines<Box<dyn BufRead>>, Errors> {
+
+        Self::decompressed_lines_on(&OsFileSystem, path)
+    }
+
+
+    /// Writes a file, compressed with gzip or zstd when its extension asks for it.
+    pub fn write_compressed(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<(), Errors> {
+
+        Self::write_compressed_on(&OsFileSystem, path, content)
+    }
+
+
+    /// Streams everything `reader` gives into a file compressed according to its extension,
+    /// returning the number of uncompressed bytes written.
+    pub fn write_compressed_from(path: impl AsRef<Path>, reader: impl Read) -> Result<u64, Errors> {
+
+        Self::write_compressed_from_on(&OsFileSystem, path, reader)
+    }
+
+
+    /// Same as `open_decompressed`, on the given file system.
+    pub fn open_decompressed_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Box<dyn BufRead>, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        let file = fs.open(path).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        let mut reader = BufReader::new(file);
+        let header = reader.fill_buf().map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+
+        Ok(match Compression::from_magic(header) {
+            Compression::None => Box::new(reader),
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            Compression::Zstd => Box::new(BufReader::new(
+                zstd::Decoder::with_buffer(reader).map_err(|e| Errors::io(Operation::Read, &[path], e))?,
+            )),
+        })
+    }
+
+
+    /// Same as `read_decompressed`, on the given file system.
+    pub fn read_decompressed_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Vec<u8>, Errors> {
+
+        let path = path.as_ref();
+        let mut content = Vec::new();
+
+        Self::open_decompressed_on(fs, path)?.read_to_end(&mut content).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        Ok(content)
+    }
+
+
+    /// Same as `decompressed_lines`, on the given file system.
+    pub fn decompressed_lines_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Lines<Box<dyn BufRead>>, Errors> {
+
+        let path = path.as_ref();
+        Ok(Lines::new(Self::open_decompressed_on(fs, path)?, path))
+    }
+
+
+    /// Same as `write_compressed`, on the given file system.
+    pub fn write_compressed_on(fs: &impl FileSystem, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<(), Errors> {
+
+        Self::write_compressed_from_on(fs, path, content.as_ref()).map(|_| ())
+    }
+
+
+    /// Same as `write_compressed_from`, on the given file system.
+    pub fn write_compressed_from_on(fs: &impl FileSystem, path: impl AsRef<Path>, mut reader: impl Read) -> Result<u64, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Write, path)?;
+
+        let file = fs.create(path).map_err(|e| Errors::io(Operation::Write, &[path], e))?;
+        let writer = BufWriter::new(file);
+
+        let written = (|| -> io::Result<u64> {
+            match Compression::from_path(path) {
+                Compression::None => {
+                    let mut writer = writer;
+                    let written = io::copy(&mut reader, &mut writer)?;
+                    writer.flush()?;
+                    Ok(written)
+                }
+                Compression::Gzip => {
+                    let mut encoder = GzEncoder::new(writer, GzipLevel::default());
+                    let written = io::copy(&mut reader, &mut encoder)?;
+                    encoder.finish()?.flush()?;
+                    Ok(written)
+                }
+                Compression::Zstd => {
+                    let mut encoder = zstd::Encoder::new(writer, ZSTD_LEVEL)?;
+                    let written = io::copy(&mut reader, &mut encoder)?;
+                    encoder.finish()?.flush()?;
+                    Ok(written)
+                }
+            }
+        })();
+
+        written.map_err(|e| Errors::io(Operation::Write, &[path], e))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::errors::ErrorKind;
+    use crate::file_system::MemoryFileSystem;
+
+    const CONTENT: &str = "first line\nsecond line\n";
+
+    /// Writes `CONTENT` to `name`, then renames it without its extension so that only its magic bytes tell how to read it.
+    fn round_trip(name: &str, magic: &[u8]) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        let renamed = dir.path().join("data");
+
+        assert_eq!(FileManager::write_compressed_from(&path, CONTENT.as_bytes()).unwrap(), CONTENT.len() as u64);
+        fs::rename(&path, &renamed).unwrap();
+
+        let raw = fs::read(&renamed).unwrap();
+        assert!(raw.starts_with(magic));
+        assert_ne!(raw, CONTENT.as_bytes());
+
+        assert_eq!(FileManager::read_decompressed(&renamed).unwrap(), CONTENT.as_bytes());
+        let lines = FileManager::decompressed_lines(&renamed).unwrap().collect::<Result<Vec<_>, Errors>>().unwrap();
+        assert_eq!(lines, ["first line", "second line"]);
+    }
+
+    #[test]
+    fn round_trips_gzip() {
+        round_trip("data.gz", GZIP_MAGIC);
+    }
+
+    #[test]
+    fn round_trips_zstd() {
+        round_trip("data.ZST", ZSTD_MAGIC);
+    }
+
+    #[test]
+    fn reads_uncompressed_files_as_they_are() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+
+        FileManager::write_compressed(&path, CONTENT).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), CONTENT.as_bytes());
+        assert_eq!(FileManager::read_decompressed(&path).unwrap(), CONTENT.as_bytes());
+    }
+
+    #[test]
+    fn reads_concatenated_gzip_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (dir.path().join("first.gz"), dir.path().join("second.gz"));
+        FileManager::write_compressed(&first, "first\n").unwrap();
+        FileManager::write_compressed(&second, "second\n").unwrap();
+
+        let joined = dir.path().join("joined.gz");
+        fs::write(&joined, [fs::read(&first).unwrap(), fs::read(&second).unwrap()].concat()).unwrap();
+
+        assert_eq!(FileManager::read_decompressed(&joined).unwrap(), b"first\nsecond\n");
+    }
+
+    #[test]
+    fn detects_compression() {
+        assert_eq!(Compression::from_path("logs/app.log.GZ"), Compression::Gzip);
+        assert_eq!(Compression::from_path("app.zstd"), Compression::Zstd);
+        assert_eq!(Compression::from_path("app.log"), Compression::None);
+        assert_eq!(Compression::from_magic(&[0x1f]), Compression::None);
+    }
+
+    #[test]
+    fn works_on_any_file_system() {
+        let fs = MemoryFileSystem::new().with_dir("/logs");
+
+        FileManager::write_compressed_on(&fs, "/logs/app.log.zst", CONTENT).unwrap();
+        assert!(fs.read(Path::new("/logs/app.log.zst")).unwrap().starts_with(ZSTD_MAGIC));
+        assert_eq!(FileManager::read_decompressed_on(&fs, "/logs/app.log.zst").unwrap(), CONTENT.as_bytes());
+
+        let dir = tempfile::tempdir().unwrap();
+        let rooted = FileManager::rooted(dir.path()).unwrap();
+        FileManager::write_compressed_on(&rooted, "app.log.gz", CONTENT).unwrap();
+        let lines = FileManager::decompressed_lines_on(&rooted, "app.log.gz").unwrap().collect::<Result<Vec<_>, Errors>>().unwrap();
+        assert_eq!(lines, ["first line", "second line"]);
+        assert_eq!(FileManager::read_decompressed_on(&rooted, "../app.log.gz").unwrap_err().kind(), ErrorKind::InvalidPath);
+    }
+}
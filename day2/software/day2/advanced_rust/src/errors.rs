@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+
+/// What went wrong, independently of the operation that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    FileNotFound,
+    PermissionDenied,
+    InvalidPath,
+    AlreadyExists,
+    IsADirectory,
+    NotADirectory,
+    DirectoryNotEmpty,
+    Other,
+}
+
+/// The file manager operation during which an error happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Read,
+    Write,
+    Copy,
+    Move,
+    Delete,
+    Watch,
+    Hash,
+    Archive,
+    Extract,
+}
+
+/// Error of a file manager operation, with the path(s) involved and the underlying `io::Error` if any.
+#[derive(Debug)]
+pub struct Errors {
+    kind: ErrorKind,
+    operation: Option<Operation>,
+    paths: Vec<PathBuf>,
+    source: Option<io::Error>,
+}
+
+
+impl Errors {
+    /// Error of `operation` on `paths`, its kind deduced from `source`.
+    pub fn io(operation: Operation, paths: &[&Path], source: io::Error) -> Self {
+
+        Errors {
+            kind: ErrorKind::from(source.kind()),
+            operation: Some(operation),
+            paths: paths.iter().map(|path| path.to_path_buf()).collect(),
+            source: Some(source),
+        }
+    }
+
+
+    /// Error detected before touching the file system, such as an empty path.
+    pub fn new(kind: ErrorKind, operation: Operation, paths: &[&Path]) -> Self {
+
+        Errors {
+            kind,
+            operation: Some(operation),
+            paths: paths.iter().map(|path| path.to_path_buf()).collect(),
+            source: None,
+        }
+    }
+
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn operation(&self) -> Option<Operation> {
+        self.operation
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+
+/// The one mapping from `io::ErrorKind` to the file manager error kinds.
+impl From<io::ErrorKind> for ErrorKind {
+    fn from(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::NotFound => ErrorKind::FileNotFound,
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => ErrorKind::PermissionDenied,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidFilename => ErrorKind::InvalidPath,
+            io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+            io::ErrorKind::IsADirectory => ErrorKind::IsADirectory,
+            io::ErrorKind::NotADirectory => ErrorKind::NotADirectory,
+            io::ErrorKind::DirectoryNotEmpty => ErrorKind::DirectoryNotEmpty,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+
+impl From<io::Error> for Errors {
+    fn from(source: io::Error) -> Self {
+        Errors {
+            kind: ErrorKind::from(source.kind()),
+            operation: None,
+            paths: Vec::new(),
+            source: Some(source),
+        }
+    }
+}
+
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::FileNotFound => write!(f, "File not found"),
+            ErrorKind::PermissionDenied => write!(f, "Permission denied"),
+            ErrorKind::InvalidPath => write!(f, "Invalid path"),
+            ErrorKind::AlreadyExists => write!(f, "Already exists"),
+            ErrorKind::IsADirectory => write!(f, "Is a directory"),
+            ErrorKind::NotADirectory => write!(f, "Not a directory"),
+            ErrorKind::DirectoryNotEmpty => write!(f, "Directory not empty"),
+            ErrorKind::Other => write!(f, "Error"),
+        }
+    }
+}
+
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::List => write!(f, "list"),
+            Operation::Read => write!(f, "read"),
+            Operation::Write => write!(f, "write"),
+            Operation::Copy => write!(f, "copy"),
+            Operation::Move => write!(f, "move"),
+            Operation::Delete => write!(f, "delete"),
+            Operation::Watch => write!(f, "watch"),
+            Operation::Hash => write!(f, "hash"),
+            Operation::Archive => write!(f, "archive"),
+            Operation::Extract => write!(f, "extract"),
+        }
+    }
+}
+
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(operation) = self.operation {
+            let paths: Vec<String> = self.paths.iter().map(|path| format!("\"{}\"", path.display())).collect();
+            write!(f, "Cannot {} {}: ", operation, paths.join(" to "))?;
+        }
+        match &self.source {
+            Some(source) => write!(f, "{}", source),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+
+impl Error for Errors {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|source| source as &(dyn Error + 'static))
+    }
+}
@@ -1,110 +1,103 @@
-use std::fmt;
-use std::fs;
-use std::io::ErrorKind;
-
-#[derive(Debug)]
-pub enum Errors {
-    FileNotFound,
-    PermissionDenied,
-    InvalidPath,
-    GeneralErrors(String),
-}
+use std::io;
+use std::path::Path;
+pub use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_system::{FileSystem, OsFileSystem};
+use crate::listing::EntryKind;
 
 pub struct FileManager;
 
-impl fmt::Display for Errors {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Errors::FileNotFound => write!(f, "File not found"),
-            Errors::PermissionDenied => write!(f, "Permission denied"),
-            Errors::InvalidPath => write!(f, "Invalid path"),
-            Errors::GeneralErrors(err)=> write!(f, "Error ! {}", err),
-        }
-    }
-}
+/// Size of the chunks files are streamed by when they are copied or hashed.
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
 
 
 impl FileManager {
-    pub fn list_files(path: &str) -> Result<Vec<String>, Errors>{
-
-        let listing = fs::read_dir(path);
-
-        match listing {
-            Ok(list) =>{
-                let mut files= Vec::new();
-                for elements in list {
-                    if let Ok(element) = elements{
-                        let is_file = element.file_type().map_err(|e| Errors::GeneralErrors(e.to_string()))?;
-                        if is_file.is_file(){
-                            let files_name = element.file_name().to_string_lossy().into_owned();
-                            files.push(files_name);
-                        }
-                    }
-                }
-                Ok(files)
-            }
-            Err(e) => {
-                let err = match e.kind() {
-                    ErrorKind::NotFound => Errors::FileNotFound,
-                    ErrorKind::PermissionDenied => Errors::PermissionDenied,
-                    ErrorKind::InvalidInput => Errors::InvalidPath,
-                    _ => Errors::GeneralErrors(e.to_string()),
-                };
-            Err(err)
-            }
-        }
+    pub fn list_files(path: impl AsRef<Path>) -> Result<Vec<String>, Errors>{
+
+        Self::list_files_on(&OsFileSystem, path)
     }
 
 
-    pub fn read_file(path: &str)-> Result<String, Errors>{
+    pub fn read_file(path: impl AsRef<Path>)-> Result<String, Errors>{
 
-        let content = fs::read_to_string(path);
+        Self::read_file_on(&OsFileSystem, path)
+    }
 
-        match content {
-            Ok(content) => Ok(content),
-            Err(e) => {let err= match e.kind() {
-                ErrorKind::NotFound => Errors::FileNotFound,
-                ErrorKind::PermissionDenied => Errors::PermissionDenied,
-                ErrorKind::InvalidInput => Errors::InvalidPath,
-                _ => Errors::GeneralErrors(e.to_string()),
-                };
-                Err(err)
-            }
-        }
+
+    pub fn write_file(path: impl AsRef<Path>, content: &str) -> Result<(), Errors> {
+
+        Self::write_file_on(&OsFileSystem, path, content)
     }
 
 
-    pub fn write_file(path: &str, content: &str) -> Result<(), Errors> {
+    pub fn copy_file(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<(), Errors>{
+
+        Self::copy_file_on(&OsFileSystem, src, dest)
+    }
 
-        let writing = fs::write(path, content);
 
-        match writing {
-            Ok(_) => Ok(()),
-            Err(e) => { let err = match e.kind() {
-                ErrorKind::NotFound => Errors::FileNotFound,
-                ErrorKind::PermissionDenied => Errors::PermissionDenied,
-                ErrorKind::InvalidInput => Errors::InvalidPath,
-                _ => Errors::GeneralErrors(e.to_string()),
-            };
-            Err(err)
+    /// Same as `list_files`, on the given file system.
+    pub fn list_files_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Vec<String>, Errors>{
+
+        let path = path.as_ref();
+        check_path(Operation::List, path)?;
+
+        let list = fs.read_dir(path).map_err(|e| Errors::io(Operation::List, &[path], e))?;
+
+        let mut files= Vec::new();
+        for element in list {
+            // Symlinks are not followed, a link to a file is not a file.
+            let metadata = fs.symlink_metadata(&element).map_err(|e| Errors::io(Operation::List, &[&element], e))?;
+            if metadata.kind == EntryKind::File && let Some(name) = element.file_name() {
+                files.push(name.to_string_lossy().into_owned());
             }
         }
+        Ok(files)
     }
 
-    pub fn copy_file(src: &str, dest: &str) -> Result<(), Errors>{
 
-        let copy = fs::copy(src, dest);
+    /// Same as `read_file`, on the given file system.
+    pub fn read_file_on(fs: &impl FileSystem, path: impl AsRef<Path>)-> Result<String, Errors>{
 
-        match copy {
-            Ok(_) => Ok(()),
-            Err(e) => { let err = match e.kind() {
-                ErrorKind::NotFound => Errors::FileNotFound,
-                ErrorKind::PermissionDenied => Errors::PermissionDenied,
-                ErrorKind::InvalidInput => Errors::InvalidPath,
-                _ => Errors::GeneralErrors(e.to_string()),
-            };
-            Err(err)
-            }
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        let content = fs.read(path).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        String::from_utf8(content).map_err(|e| Errors::io(Operation::Read, &[path], io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+
+    /// Same as `write_file`, on the given file system.
+    pub fn write_file_on(fs: &impl FileSystem, path: impl AsRef<Path>, content: &str) -> Result<(), Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Write, path)?;
+
+        fs.write(path, content.as_bytes()).map_err(|e| Errors::io(Operation::Write, &[path], e))
+    }
+
+
+    /// Same as `copy_file`, on the given file system.
+    pub fn copy_file_on(fs: &impl FileSystem, src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<(), Errors>{
+
+        let (src, dest) = (src.as_ref(), dest.as_ref());
+        check_path(Operation::Copy, src)?;
+        check_path(Operation::Copy, dest)?;
+
+        // fs::copy reports a directory source as InvalidInput, name it properly.
+        if fs.is_dir(src) {
+            return Err(Errors::new(ErrorKind::IsADirectory, Operation::Copy, &[src, dest]));
         }
+
+        fs.copy(src, dest).map(|_| ()).map_err(|e| Errors::io(Operation::Copy, &[src, dest], e))
     }
-}
\ No newline at end of file
+}
+
+
+/// Rejects paths no operation can succeed on: empty ones and ones containing a NUL byte.
+pub(crate) fn check_path(operation: Operation, path: &Path) -> Result<(), Errors> {
+
+    if path.as_os_str().is_empty() || path.as_os_str().as_encoded_bytes().contains(&0) {
+        return Err(Errors::new(ErrorKind::InvalidPath, operation, &[path]));
+    }
+    Ok(())
+}
@@ -0,0 +1,631 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, FileTimes};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::listing::EntryKind;
+use crate::writing::{self, WriteMode};
+
+
+/// The file system calls `FileManager` is built on, so that its callers can be tested without touching the disk.
+/// Implementations report failures as plain `io::Error`s, `FileManager` turns them into `Errors`.
+pub trait FileSystem {
+    /// Paths of the direct children of a directory.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Metadata of an entry. A symlink is described itself, not the entry it points to.
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    fn is_file(&self, path: &Path) -> bool;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Opens a file for reading.
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>>;
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+
+        self.write_with(path, content, WriteMode::Truncate)
+    }
+
+    /// Writes a file as `mode` says.
+    fn write_with(&self, path: &Path, content: &[u8], mode: WriteMode) -> io::Result<()>;
+
+    /// Creates or truncates a file, to write it in several steps.
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>>;
+
+    /// Copies a file, returning the number of bytes copied.
+    fn copy(&self, src: &Path, dest: &Path) -> io::Result<u64> {
+
+        let content = self.read(src)?;
+        self.write(dest, &content)?;
+        Ok(content.len() as u64)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn rename(&self, src: &Path, dest: &Path) -> io::Result<()>;
+
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes a directory and everything under it.
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Target of a symlink. Fails with `Unsupported` where there are no symlinks.
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Creates the symlink `link` pointing to `target`. Fails with `Unsupported` where there are no symlinks.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// Sets the permission bits of an entry, see `Metadata::mode`.
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()>;
+
+    /// Sets the access and modification times of an entry, those given as None being left as they are.
+    fn set_times(&self, path: &Path, accessed: Option<SystemTime>, modified: Option<SystemTime>) -> io::Result<()>;
+}
+
+/// A file opened by `FileSystem::open`, read in order or at any offset.
+pub trait ReadSeek: Read + Seek {}
+
+/// What `FileSystem::symlink_metadata` tells about an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub kind: EntryKind,
+    pub len: u64,
+    /// Unix permission bits, such as 0o644. Elsewhere only read-only entries are told apart:
+    /// they are reported as 0o444, the others as 0o644, or 0o755 for directories.
+    pub mode: u32,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+/// The real file system, used by the `FileManager` functions that do not take one.
+pub struct OsFileSystem;
+
+/// A file system held in memory, starting empty. Paths marked with `deny` fail with `PermissionDenied`,
+/// along with everything under them. It has no symlinks, and its entries have neither permissions nor
+/// timestamps: files are reported as 0o644 and directories as 0o755, and setting them does nothing.
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    nodes: RefCell<BTreeMap<PathBuf, Node>>,
+    denied: BTreeSet<PathBuf>,
+}
+
+enum Node {
+    File(Vec<u8>),
+    Directory,
+}
+
+/// Writer of `MemoryFileSystem::create`, adding what it is given to the file as it goes.
+struct MemoryWriter<'a> {
+    fs: &'a MemoryFileSystem,
+    path: PathBuf,
+}
+
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+
+impl FileSystem for OsFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?.map(|element| element.map(|element| element.path())).collect()
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = fs::symlink_metadata(path)?;
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else if file_type.is_dir() {
+            EntryKind::Directory
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else {
+            EntryKind::Other
+        };
+
+        Ok(Metadata {
+            kind,
+            len: metadata.len(),
+            mode: mode(kind, &metadata.permissions()),
+            accessed: metadata.accessed().ok(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn write_with(&self, path: &Path, content: &[u8], mode: WriteMode) -> io::Result<()> {
+        match mode {
+            WriteMode::Truncate => fs::write(path, content),
+            WriteMode::Append => fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(content),
+            WriteMode::CreateNew => fs::OpenOptions::new().write(true).create_new(true).open(path)?.write_all(content),
+            WriteMode::Atomic => writing::write_atomic(path, content),
+        }
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(File::create(path)?))
+    }
+
+    fn copy(&self, src: &Path, dest: &Path) -> io::Result<u64> {
+        fs::copy(src, dest)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn rename(&self, src: &Path, dest: &Path) -> io::Result<()> {
+        fs::rename(src, dest)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        symlink(target, link)
+    }
+
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        set_permissions(path, mode)
+    }
+
+    fn set_times(&self, path: &Path, accessed: Option<SystemTime>, modified: Option<SystemTime>) -> io::Result<()> {
+        let mut times = FileTimes::new();
+        if let Some(accessed) = accessed {
+            times = times.set_accessed(accessed);
+        }
+        if let Some(modified) = modified {
+            times = times.set_modified(modified);
+        }
+        File::open(path)?.set_times(times)
+    }
+}
+
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, creating its parent directories.
+    pub fn with_file(self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Self {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            self.add_directories(parent);
+        }
+        self.nodes.borrow_mut().insert(path.to_path_buf(), Node::File(content.as_ref().to_vec()));
+        self
+    }
+
+    /// Adds a directory and its parents.
+    pub fn with_dir(self, path: impl AsRef<Path>) -> Self {
+        self.add_directories(path.as_ref());
+        self
+    }
+
+    /// Makes every access to `path` and what is under it fail with `PermissionDenied`.
+    pub fn deny(mut self, path: impl AsRef<Path>) -> Self {
+        self.denied.insert(path.as_ref().to_path_buf());
+        self
+    }
+
+    fn add_directories(&self, path: &Path) {
+        for ancestor in path.ancestors().filter(|ancestor| !ancestor.as_os_str().is_empty()) {
+            self.nodes.borrow_mut().entry(ancestor.to_path_buf()).or_insert(Node::Directory);
+        }
+    }
+
+    fn check_access(&self, path: &Path) -> io::Result<()> {
+        if self.denied.iter().any(|denied| path.starts_with(denied)) {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        }
+        Ok(())
+    }
+
+    /// Checks that a file can be created at `path`.
+    fn check_new_file(&self, path: &Path) -> io::Result<()> {
+        self.check_access(path)?;
+        if self.is_dir(path) {
+            return Err(io::Error::from(io::ErrorKind::IsADirectory));
+        }
+        self.check_parent(path)
+    }
+
+    /// Checks that the parent of `path` is a directory.
+    fn check_parent(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            if self.is_file(parent) {
+                return Err(io::Error::from(io::ErrorKind::NotADirectory));
+            }
+            if !self.is_dir(parent) {
+                return Err(io::Error::from(io::ErrorKind::NotFound));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `path` exists.
+    fn check_exists(&self, path: &Path) -> io::Result<()> {
+        self.check_access(path)?;
+        if !Self::is_root(path) && !self.nodes.borrow().contains_key(path) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        Ok(())
+    }
+
+    /// The root of relative and absolute paths always exists.
+    fn is_root(path: &Path) -> bool {
+        path.as_os_str().is_empty() || path.parent().is_none()
+    }
+}
+
+
+impl FileSystem for MemoryFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.check_access(path)?;
+        match self.nodes.borrow().get(path) {
+            Some(Node::File(_)) => return Err(io::Error::from(io::ErrorKind::NotADirectory)),
+            None if !Self::is_root(path) => return Err(io::Error::from(io::ErrorKind::NotFound)),
+            _ => {}
+        }
+        Ok(self.nodes.borrow().keys().filter(|child| child.parent() == Some(path)).cloned().collect())
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.check_exists(path)?;
+        let (kind, len, mode) = match self.nodes.borrow().get(path) {
+            Some(Node::File(content)) => (EntryKind::File, content.len() as u64, 0o644),
+            _ => (EntryKind::Directory, 0, 0o755),
+        };
+        Ok(Metadata { kind, len, mode, accessed: None, modified: None })
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        matches!(self.nodes.borrow().get(path), Some(Node::File(_)))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        Self::is_root(path) || matches!(self.nodes.borrow().get(path), Some(Node::Directory))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.check_access(path)?;
+        match self.nodes.borrow().get(path) {
+            Some(Node::File(content)) => Ok(content.clone()),
+            Some(Node::Directory) => Err(io::Error::from(io::ErrorKind::IsADirectory)),
+            None => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        Ok(Box::new(Cursor::new(self.read(path)?)))
+    }
+
+    fn write_with(&self, path: &Path, content: &[u8], mode: WriteMode) -> io::Result<()> {
+        self.check_new_file(path)?;
+        let mut nodes = self.nodes.borrow_mut();
+        match (mode, nodes.get_mut(path)) {
+            (WriteMode::CreateNew, Some(_)) => return Err(io::Error::from(io::ErrorKind::AlreadyExists)),
+            (WriteMode::Append, Some(Node::File(existing))) => existing.extend_from_slice(content),
+            _ => {
+                nodes.insert(path.to_path_buf(), Node::File(content.to_vec()));
+            }
+        }
+        Ok(())
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>> {
+        self.write(path, &[])?;
+        Ok(Box::new(MemoryWriter { fs: self, path: path.to_path_buf() }))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check_access(path)?;
+        if let Some(file) = path.ancestors().find(|ancestor| self.is_file(ancestor)) {
+            let kind = if file == path {io::ErrorKind::AlreadyExists} else {io::ErrorKind::NotADirectory};
+            return Err(io::Error::from(kind));
+        }
+        self.add_directories(path);
+        Ok(())
+    }
+
+    fn rename(&self, src: &Path, dest: &Path) -> io::Result<()> {
+        self.check_exists(src)?;
+        self.check_access(dest)?;
+        self.check_parent(dest)?;
+        if dest.starts_with(src) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        if self.nodes.borrow().contains_key(dest) {
+            match (self.is_dir(src), self.is_dir(dest)) {
+                (false, true) => return Err(io::Error::from(io::ErrorKind::IsADirectory)),
+                (true, false) => return Err(io::Error::from(io::ErrorKind::NotADirectory)),
+                (true, true) if !self.read_dir(dest)?.is_empty() => return Err(io::Error::from(io::ErrorKind::DirectoryNotEmpty)),
+                _ => {}
+            }
+        }
+
+        let mut nodes = self.nodes.borrow_mut();
+        let moved: Vec<PathBuf> = nodes.keys().filter(|path| path.starts_with(src)).cloned().collect();
+        for path in moved {
+            let node = nodes.remove(&path).unwrap();
+            nodes.insert(dest.join(path.strip_prefix(src).unwrap()), node);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.check_exists(path)?;
+        if self.is_dir(path) {
+            return Err(io::Error::from(io::ErrorKind::IsADirectory));
+        }
+        self.nodes.borrow_mut().remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check_exists(path)?;
+        if self.is_file(path) {
+            return Err(io::Error::from(io::ErrorKind::NotADirectory));
+        }
+        self.nodes.borrow_mut().retain(|node, _| !node.starts_with(path));
+        Ok(())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.check_exists(path)?;
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    fn symlink(&self, _target: &Path, link: &Path) -> io::Result<()> {
+        self.check_access(link)?;
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    fn set_permissions(&self, path: &Path, _mode: u32) -> io::Result<()> {
+        self.check_exists(path)
+    }
+
+    fn set_times(&self, path: &Path, _accessed: Option<SystemTime>, _modified: Option<SystemTime>) -> io::Result<()> {
+        self.check_exists(path)
+    }
+}
+
+
+impl Write for MemoryWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.fs.write_with(&self.path, data, WriteMode::Append)?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+
+#[cfg(unix)]
+fn mode(_kind: EntryKind, permissions: &fs::Permissions) -> u32 {
+
+    use std::os::unix::fs::PermissionsExt;
+
+    permissions.mode() & 0o7777
+}
+
+
+#[cfg(not(unix))]
+fn mode(kind: EntryKind, permissions: &fs::Permissions) -> u32 {
+
+    match (kind, permissions.readonly()) {
+        (_, true) => 0o444,
+        (EntryKind::Directory, false) => 0o755,
+        (_, false) => 0o644,
+    }
+}
+
+
+#[cfg(unix)]
+fn set_permissions(path: &Path, mode: u32) -> io::Result<()> {
+
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))
+}
+
+
+/// Only the read-only flag can be set on other platforms, from the write bits of `mode`.
+#[cfg(not(unix))]
+fn set_permissions(path: &Path, mode: u32) -> io::Result<()> {
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+
+    std::os::unix::fs::symlink(target, link)
+}
+
+
+#[cfg(not(unix))]
+fn symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ErrorKind, Operation};
+    use crate::file_manager::FileManager;
+    use crate::transfer::Progress;
+
+    fn fake() -> MemoryFileSystem {
+        MemoryFileSystem::new()
+            .with_file("docs/readme.txt", "Hello")
+            .with_file("docs/notes.txt", "Notes")
+            .with_dir("docs/archive")
+            .with_file("secret/key.txt", "hunter2")
+            .deny("secret")
+    }
+
+    #[test]
+    fn works_on_the_fake() {
+        let fs = fake();
+
+        FileManager::copy_file_on(&fs, "docs/readme.txt", "docs/copy.txt").unwrap();
+        FileManager::write_file_on(&fs, "docs/new.txt", "New").unwrap();
+
+        assert_eq!(FileManager::read_file_on(&fs, "docs/copy.txt").unwrap(), "Hello");
+        assert_eq!(FileManager::list_files_on(&fs, "docs").unwrap(), ["copy.txt", "new.txt", "notes.txt", "readme.txt"]);
+    }
+
+    #[test]
+    fn maps_missing_files() {
+        let error = FileManager::read_file_on(&fake(), "docs/missing.txt").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::FileNotFound);
+        assert_eq!(error.operation(), Some(Operation::Read));
+        assert_eq!(error.paths(), [PathBuf::from("docs/missing.txt")]);
+        assert_eq!(FileManager::list_files_on(&fake(), "missing").unwrap_err().kind(), ErrorKind::FileNotFound);
+        assert_eq!(FileManager::write_file_on(&fake(), "missing/new.txt", "").unwrap_err().kind(), ErrorKind::FileNotFound);
+    }
+
+    #[test]
+    fn maps_permission_errors() {
+        let fs = fake();
+
+        assert_eq!(FileManager::read_file_on(&fs, "secret/key.txt").unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(FileManager::list_files_on(&fs, "secret").unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(FileManager::write_file_on(&fs, "secret/new.txt", "").unwrap_err().kind(), ErrorKind::PermissionDenied);
+
+        let error = FileManager::copy_file_on(&fs, "secret/key.txt", "docs/stolen.txt").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(error.paths(), [PathBuf::from("secret/key.txt"), PathBuf::from("docs/stolen.txt")]);
+    }
+
+    #[test]
+    fn maps_directory_mismatches() {
+        let fs = fake();
+
+        assert_eq!(FileManager::read_file_on(&fs, "docs").unwrap_err().kind(), ErrorKind::IsADirectory);
+        assert_eq!(FileManager::copy_file_on(&fs, "docs", "copy").unwrap_err().kind(), ErrorKind::IsADirectory);
+        assert_eq!(FileManager::write_file_on(&fs, "docs/archive", "").unwrap_err().kind(), ErrorKind::IsADirectory);
+        assert_eq!(FileManager::list_files_on(&fs, "docs/readme.txt").unwrap_err().kind(), ErrorKind::NotADirectory);
+        assert_eq!(FileManager::write_file_on(&fs, "docs/readme.txt/new.txt", "").unwrap_err().kind(), ErrorKind::NotADirectory);
+    }
+
+    #[test]
+    fn rejects_invalid_paths_before_reaching_the_file_system() {
+        let error = FileManager::read_file_on(&fake(), "").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidPath);
+        assert!(std::error::Error::source(&error).is_none());
+    }
+
+    #[test]
+    fn reports_invalid_utf8_as_other_errors() {
+        let fs = fake().with_file("binary.bin", [0xff, 0xfe]);
+
+        assert_eq!(FileManager::read_file_on(&fs, "binary.bin").unwrap_err().kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn lists_recursively_on_the_fake() {
+        let fs = fake();
+        let entries: Vec<_> = FileManager::list_on(&fs, "docs").recursive().into_iter().map(|entry| entry.unwrap()).collect();
+
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path.clone()).collect();
+        assert_eq!(paths, [PathBuf::from("docs/archive"), PathBuf::from("docs/notes.txt"), PathBuf::from("docs/readme.txt")]);
+        assert_eq!(entries[0].kind, EntryKind::Directory);
+        assert_eq!(entries[2].size, 5);
+
+        let denied = FileManager::list_on(&fs, "secret").into_iter().next().unwrap().unwrap_err();
+        assert_eq!(denied.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn writes_with_every_mode_on_the_fake() {
+        let fs = fake();
+
+        FileManager::write_file_with_on(&fs, "docs/log.txt", "one\n", WriteMode::CreateNew).unwrap();
+        FileManager::write_file_with_on(&fs, "docs/log.txt", "two\n", WriteMode::Append).unwrap();
+        assert_eq!(FileManager::read_file_on(&fs, "docs/log.txt").unwrap(), "one\ntwo\n");
+
+        let error = FileManager::write_file_with_on(&fs, "docs/log.txt", "", WriteMode::CreateNew).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+
+        FileManager::write_file_with_on(&fs, "docs/log.txt", "replaced", WriteMode::Atomic).unwrap();
+        assert_eq!(FileManager::read_file_on(&fs, "docs/log.txt").unwrap(), "replaced");
+    }
+
+    #[test]
+    fn reads_and_streams_on_the_fake() {
+        let fs = fake().with_file("data/lines.txt", "first\r\nsecond\nthird");
+
+        assert_eq!(FileManager::read_range_on(&fs, "data/lines.txt", 7, 6).unwrap(), b"second");
+        let lines: Vec<String> = FileManager::lines_on(&fs, "data/lines.txt").unwrap().map(Result::unwrap).collect();
+        assert_eq!(lines, ["first", "second", "third"]);
+        let chunks: Vec<Vec<u8>> = FileManager::chunks_on(&fs, "docs/readme.txt", 2).unwrap().map(Result::unwrap).collect();
+        assert_eq!(chunks, [b"He".to_vec(), b"ll".to_vec(), b"o".to_vec()]);
+
+        assert_eq!(FileManager::write_from_on(&fs, "data/copy.txt", &b"streamed"[..]).unwrap(), 8);
+        assert_eq!(FileManager::read_bytes_on(&fs, "data/copy.txt").unwrap(), b"streamed");
+    }
+
+    #[test]
+    fn copies_and_moves_directories_on_the_fake() {
+        let fs = fake();
+
+        let progress = FileManager::copy_dir_on(&fs, "docs", "backup").run().unwrap();
+        assert_eq!(progress, Progress { bytes_done: 10, bytes_total: 10, files_done: 2, files_total: 2 });
+        assert_eq!(FileManager::read_file_on(&fs, "backup/readme.txt").unwrap(), "Hello");
+        assert!(fs.is_dir(Path::new("backup/archive")));
+
+        FileManager::move_path_on(&fs, "backup", "moved").run().unwrap();
+        assert!(!fs.is_dir(Path::new("backup")));
+        assert_eq!(FileManager::list_files_on(&fs, "moved").unwrap(), ["notes.txt", "readme.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_list_symlinks_as_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), "content").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("file.txt"), dir.path().join("link.txt")).unwrap();
+
+        assert_eq!(FileManager::list_files(dir.path()).unwrap(), ["file.txt"]);
+    }
+}
@@ -0,0 +1,195 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::listing::{Entry, EntryKind, relative_name};
+
+
+/// A listing by glob patterns, configured with its builder methods and run with `entries`.
+pub struct Glob {
+    root: PathBuf,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+    case_insensitive: bool,
+}
+
+
+impl FileManager {
+    /// Lists what is under `root` matching glob patterns, such as `src/**/*.rs`, relative to `root`.
+    pub fn glob(root: impl AsRef<Path>) -> Glob {
+
+        Glob { root: root.as_ref().to_path_buf(), includes: Vec::new(), excludes: Vec::new(), case_insensitive: false }
+    }
+}
+
+
+impl Glob {
+    /// Adds a pattern to include, or to exclude if it starts with `!` (e.g. `!**/target/**`).
+    /// Without any pattern to include, everything is.
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        match pattern.strip_prefix('!') {
+            Some(excluded) => self.excludes.push(excluded.to_string()),
+            None => self.includes.push(pattern.to_string()),
+        }
+        self
+    }
+
+    pub fn patterns<'a>(self, patterns: impl IntoIterator<Item = &'a str>) -> Self {
+        patterns.into_iter().fold(self, Glob::pattern)
+    }
+
+    /// Adds a pattern to exclude. An excluded directory excludes everything under it, and a pattern
+    /// excluding everything under a directory, like `**/target/**`, excludes the directory too.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.excludes.push(pattern.to_string());
+        self
+    }
+
+    /// Matches the patterns whatever the case of the paths.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+
+    /// Files and directories matching the patterns, sorted by path. `*` and `?` do not match `/`, `**` does.
+    pub fn entries(self) -> Result<Vec<Entry>, Errors> {
+
+        check_path(Operation::List, &self.root)?;
+
+        let includes = if self.includes.is_empty() { vec!["**".to_string()] } else { self.includes.clone() };
+        let includes = self.build(&includes)?;
+        let excludes = Arc::new(self.build(&self.excludes)?);
+
+        let root = self.root.clone();
+        let prune = {
+            let (root, excludes) = (root.clone(), Arc::clone(&excludes));
+            move |entry: &Entry| is_excluded(&excludes, entry, &relative_name(&root, &entry.path))
+        };
+
+        let mut entries = Vec::new();
+        for entry in FileManager::list(&root).recursive().hidden(true).prune(prune) {
+            let entry = entry?;
+            let path = relative_name(&root, &entry.path);
+            if includes.is_match(&path) && !is_excluded(&excludes, &entry, &path) {
+                entries.push(entry);
+            }
+        }
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+
+    /// Paths of the files matching the patterns, sorted.
+    pub fn files(self) -> Result<Vec<PathBuf>, Errors> {
+
+        Ok(self.entries()?.into_iter().filter(|entry| entry.kind == EntryKind::File).map(|entry| entry.path).collect())
+    }
+
+
+    fn build(&self, patterns: &[String]) -> Result<GlobSet, Errors> {
+
+        let invalid = |pattern: &str, e: globset::Error| {
+            Errors::io(Operation::List, &[Path::new(pattern)], io::Error::new(io::ErrorKind::InvalidInput, e))
+        };
+
+        let mut set = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .case_insensitive(self.case_insensitive)
+                .build()
+                .map_err(|e| invalid(pattern, e))?;
+            set.add(glob);
+        }
+        set.build().map_err(|e| invalid(&patterns.join(", "), e))
+    }
+}
+
+
+/// Whether the excluded patterns match an entry, a directory being matched with a trailing `/`
+/// as well so that `target/**` excludes `target` itself.
+fn is_excluded(excludes: &GlobSet, entry: &Entry, path: &str) -> bool {
+
+    excludes.is_match(path) || (entry.kind == EntryKind::Directory && excludes.is_match(format!("{}/", path)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use crate::errors::ErrorKind;
+
+    fn tree() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["Cargo.toml", "src/main.rs", "src/B.RS", "src/x/a.rs", "target/debug/app", ".git/config"] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        dir
+    }
+
+    fn names(dir: &TempDir, glob: Glob) -> Vec<String> {
+        glob.entries().unwrap().iter().map(|entry| relative_name(dir.path(), &entry.path)).collect()
+    }
+
+    #[test]
+    fn lists_everything_without_patterns() {
+        let dir = tree();
+
+        assert_eq!(names(&dir, FileManager::glob(dir.path())), [
+            ".git", ".git/config", "Cargo.toml", "src", "src/B.RS", "src/main.rs", "src/x", "src/x/a.rs", "target", "target/debug", "target/debug/app",
+        ]);
+    }
+
+    #[test]
+    fn includes_by_pattern() {
+        let dir = tree();
+
+        assert_eq!(names(&dir, FileManager::glob(dir.path()).pattern("src/*.rs")), ["src/main.rs"]);
+        assert_eq!(names(&dir, FileManager::glob(dir.path()).pattern("**/*.rs")), ["src/main.rs", "src/x/a.rs"]);
+        assert_eq!(names(&dir, FileManager::glob(dir.path()).patterns(["*.toml", "src/x/*"])), ["Cargo.toml", "src/x/a.rs"]);
+    }
+
+    #[test]
+    fn excludes_directories_with_their_content() {
+        let dir = tree();
+
+        let glob = FileManager::glob(dir.path()).patterns(["!**/target/**", "!.git/**"]);
+        assert_eq!(names(&dir, glob), ["Cargo.toml", "src", "src/B.RS", "src/main.rs", "src/x", "src/x/a.rs"]);
+
+        let glob = FileManager::glob(dir.path()).pattern("**").exclude("src/x");
+        assert!(!names(&dir, glob).iter().any(|name| name.starts_with("src/x")));
+    }
+
+    #[test]
+    fn matches_case_insensitively_on_demand() {
+        let dir = tree();
+
+        assert_eq!(names(&dir, FileManager::glob(dir.path()).pattern("src/*.rs")), ["src/main.rs"]);
+        assert_eq!(names(&dir, FileManager::glob(dir.path()).pattern("src/*.rs").case_insensitive(true)), ["src/B.RS", "src/main.rs"]);
+    }
+
+    #[test]
+    fn returns_only_files_sorted() {
+        let dir = tree();
+        let files = FileManager::glob(dir.path()).pattern("!target/**").files().unwrap();
+
+        let expected: Vec<PathBuf> = [".git/config", "Cargo.toml", "src/B.RS", "src/main.rs", "src/x/a.rs"].iter().map(|file| dir.path().join(file)).collect();
+        assert_eq!(files, expected);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let dir = tree();
+        let error = FileManager::glob(dir.path()).pattern("src/[").entries().unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidPath);
+    }
+}
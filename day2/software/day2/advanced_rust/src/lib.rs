@@ -0,0 +1,15 @@
+pub mod archives;
+pub mod async_file_manager;
+pub mod batch;
+pub mod checksums;
+pub mod compression;
+pub mod errors;
+pub mod file_manager;
+pub mod file_system;
+pub mod globbing;
+pub mod listing;
+pub mod reading;
+pub mod rooted;
+pub mod transfer;
+pub mod watching;
+pub mod writing;
@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+use std::vec;
+use chrono::{DateTime, Local};
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::file_system::{FileSystem, Metadata, OsFileSystem};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+/// A file system entry found while listing, with its metadata. Symlinks are reported, not followed.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// 1 for the direct children of the listed directory, 2 for their children, and so on.
+    pub depth: usize,
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Permission bits, see `Metadata::mode`.
+    pub mode: u32,
+    pub modified: Option<DateTime<Local>>,
+}
+
+type Predicate = Box<dyn Fn(&Entry) -> bool>;
+
+/// Options of a listing, built with `FileManager::list` and iterated lazily.
+pub struct Listing<'a> {
+    fs: &'a dyn FileSystem,
+    root: PathBuf,
+    max_depth: Option<usize>,
+    hidden: bool,
+    filters: Vec<Predicate>,
+    pruned: Vec<Predicate>,
+}
+
+/// Iterator over the entries of a `Listing`, depth first and sorted by name within each directory.
+pub struct Entries<'a> {
+    listing: Listing<'a>,
+    started: bool,
+    stack: Vec<(vec::IntoIter<PathBuf>, usize)>,
+    deferred: Option<Errors>,
+}
+
+
+impl FileManager {
+    /// Lists the entries of `path`, only its direct children unless `recursive` or `max_depth` is used.
+    pub fn list(path: impl AsRef<Path>) -> Listing<'static> {
+
+        Self::list_on(&OsFileSystem, path)
+    }
+
+
+    /// Same as `list`, on the given file system.
+    pub fn list_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Listing<'_> {
+
+        Listing::new(fs, path.as_ref())
+    }
+}
+
+
+impl Entry {
+    pub fn name(&self) -> String {
+        self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        is_hidden(&self.path)
+    }
+
+    fn new(path: PathBuf, depth: usize, metadata: Metadata) -> Self {
+
+        Entry {
+            path,
+            depth,
+            kind: metadata.kind,
+            size: metadata.len,
+            mode: metadata.mode,
+            modified: metadata.modified.map(DateTime::<Local>::from),
+        }
+    }
+}
+
+
+impl<'a> Listing<'a> {
+    pub(crate) fn new(fs: &'a dyn FileSystem, root: &Path) -> Self {
+
+        Listing {
+            fs,
+            root: root.to_path_buf(),
+            max_depth: Some(1),
+            hidden: false,
+            filters: Vec::new(),
+            pruned: Vec::new(),
+        }
+    }
+
+
+    /// Descends into every subdirectory.
+    pub fn recursive(mut self) -> Self {
+        self.max_depth = None;
+        self
+    }
+
+    /// Descends at most `depth` levels, 1 listing only the direct children.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Includes the entries whose name starts with a dot, and descends into hidden directories.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Only yields the entries matching `predicate`. Directories filtered out are still descended into.
+    pub fn filter(mut self, predicate: impl Fn(&Entry) -> bool + 'static) -> Self {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Neither yields nor descends into the directories matching `predicate`.
+    pub fn prune(mut self, predicate: impl Fn(&Entry) -> bool + 'static) -> Self {
+        self.pruned.push(Box::new(predicate));
+        self
+    }
+}
+
+
+impl<'a> IntoIterator for Listing<'a> {
+    type Item = Result<Entry, Errors>;
+    type IntoIter = Entries<'a>;
+
+    fn into_iter(self) -> Entries<'a> {
+        Entries { listing: self, started: false, stack: Vec::new(), deferred: None }
+    }
+}
+
+
+impl Iterator for Entries<'_> {
+    type Item = Result<Entry, Errors>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        if !self.started {
+            self.started = true;
+            if let Err(e) = check_path(Operation::List, &self.listing.root) {
+                return Some(Err(e));
+            }
+            match read_sorted(self.listing.fs, &self.listing.root) {
+                Ok(children) => self.stack.push((children.into_iter(), 1)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        loop {
+            if let Some(e) = self.deferred.take() {
+                return Some(Err(e));
+            }
+
+            let (children, depth) = self.stack.last_mut()?;
+            let depth = *depth;
+            let Some(path) = children.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            if !self.listing.hidden && is_hidden(&path) {
+                continue;
+            }
+
+            let metadata = match self.listing.fs.symlink_metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => return Some(Err(Errors::io(Operation::List, &[&path], e))),
+            };
+            let entry = Entry::new(path, depth, metadata);
+
+            if entry.kind == EntryKind::Directory && self.listing.pruned.iter().any(|prune| prune(&entry)) {
+                continue;
+            }
+
+            if entry.kind == EntryKind::Directory && self.listing.max_depth.is_none_or(|max| depth < max) {
+                match read_sorted(self.listing.fs, &entry.path) {
+                    Ok(children) => self.stack.push((children.into_iter(), depth + 1)),
+                    Err(e) => self.deferred = Some(e),
+                }
+            }
+
+            if self.listing.filters.iter().all(|filter| filter(&entry)) {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+
+fn read_sorted(fs: &dyn FileSystem, path: &Path) -> Result<Vec<PathBuf>, Errors> {
+
+    let mut children = fs.read_dir(path).map_err(|e| Errors::io(Operation::List, &[path], e))?;
+
+    children.sort();
+    Ok(children)
+}
+
+
+/// Path of an entry relative to the listed root, with `/` separators whatever the platform.
+pub(crate) fn relative_name(root: &Path, path: &Path) -> String {
+
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+
+fn is_hidden(path: &Path) -> bool {
+
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
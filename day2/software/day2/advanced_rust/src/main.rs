@@ -1,11 +1,18 @@
-mod file_manager;
-use crate::file_manager::FileManager;
+use advanced_rust::file_manager::FileManager;
+use advanced_rust::listing::EntryKind;
 
 fn main() {
     match FileManager::list_files("./src") {
         Ok(files) => println!("\nFiles found : {:?}\n", files),
         Err(e) => eprintln!("\nError : {}\n", e),
     }
+    for entry in FileManager::list(".").max_depth(2).filter(|entry| entry.kind == EntryKind::File) {
+        match entry {
+            Ok(entry) => println!("{:>8} {} {}", entry.size, entry.modified.map(|at| at.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(), entry.path.display()),
+            Err(e) => eprintln!("Error : {}", e),
+        }
+    }
+    println!();
     match FileManager::read_file("./Cargo.toml") {
         Ok(content) => println!("Cargo.toml : {}\n", content),
         Err(e) => eprintln!("Error : {}\n", e),
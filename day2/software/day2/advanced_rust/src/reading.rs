@@ -0,0 +1,234 @@
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::errors::{Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem, ReadSeek};
+
+
+/// Iterator over the lines of a file, without their line ending. Invalid UTF-8 is replaced
+/// with U+FFFD so that one bad byte does not stop the whole file from being read.
+pub struct Lines<R = BufReader<Box<dyn ReadSeek>>> {
+    reader: R,
+    path: PathBuf,
+    buffer: Vec<u8>,
+}
+
+/// Iterator over the content of a file in chunks of at most a given size.
+pub struct Chunks<R = Box<dyn ReadSeek>> {
+    reader: R,
+    path: PathBuf,
+    size: usize,
+}
+
+
+impl FileManager {
+    pub fn read_bytes(path: impl AsRef<Path>) -> Result<Vec<u8>, Errors> {
+
+        Self::read_bytes_on(&OsFileSystem, path)
+    }
+
+
+    /// Reads `length` bytes starting at `offset`, fewer if the file ends before.
+    pub fn read_range(path: impl AsRef<Path>, offset: u64, length: usize) -> Result<Vec<u8>, Errors> {
+
+        Self::read_range_on(&OsFileSystem, path, offset, length)
+    }
+
+
+    pub fn lines(path: impl AsRef<Path>) -> Result<Lines, Errors> {
+
+        Self::lines_on(&OsFileSystem, path)
+    }
+
+
+    pub fn chunks(path: impl AsRef<Path>, size: usize) -> Result<Chunks, Errors> {
+
+        Self::chunks_on(&OsFileSystem, path, size)
+    }
+
+
+    /// Streams everything `reader` gives into the file, replacing its content, and returns the number of bytes written.
+    pub fn write_from(path: impl AsRef<Path>, reader: impl Read) -> Result<u64, Errors> {
+
+        Self::write_from_on(&OsFileSystem, path, reader)
+    }
+
+
+    /// Same as `read_bytes`, on the given file system.
+    pub fn read_bytes_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Vec<u8>, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        fs.read(path).map_err(|e| Errors::io(Operation::Read, &[path], e))
+    }
+
+
+    /// Same as `read_range`, on the given file system.
+    pub fn read_range_on(fs: &impl FileSystem, path: impl AsRef<Path>, offset: u64, length: usize) -> Result<Vec<u8>, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        let mut file = fs.open(path).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+
+        let mut content = Vec::new();
+        file.take(length as u64).read_to_end(&mut content).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        Ok(content)
+    }
+
+
+    /// Same as `lines`, on the given file system.
+    pub fn lines_on(fs: &impl FileSystem, path: impl AsRef<Path>) -> Result<Lines, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        let file = fs.open(path).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        Ok(Lines::new(BufReader::new(file), path))
+    }
+
+
+    /// Same as `chunks`, on the given file system.
+    pub fn chunks_on(fs: &impl FileSystem, path: impl AsRef<Path>, size: usize) -> Result<Chunks, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Read, path)?;
+
+        if size == 0 {
+            return Err(Errors::io(Operation::Read, &[path], io::Error::other("chunk size must not be zero")));
+        }
+
+        let file = fs.open(path).map_err(|e| Errors::io(Operation::Read, &[path], e))?;
+        Ok(Chunks::new(file, path, size))
+    }
+
+
+    /// Same as `write_from`, on the given file system.
+    pub fn write_from_on(fs: &impl FileSystem, path: impl AsRef<Path>, mut reader: impl Read) -> Result<u64, Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Write, path)?;
+
+        let file = fs.create(path).map_err(|e| Errors::io(Operation::Write, &[path], e))?;
+        let mut writer = BufWriter::new(file);
+
+        let written = io::copy(&mut reader, &mut writer).map_err(|e| Errors::io(Operation::Write, &[path], e))?;
+        writer.flush().map_err(|e| Errors::io(Operation::Write, &[path], e))?;
+        Ok(written)
+    }
+}
+
+
+impl<R: BufRead> Lines<R> {
+    /// Lines of any buffered reader, `path` being used in the errors.
+    pub fn new(reader: R, path: &Path) -> Self {
+        Lines { reader, path: path.to_path_buf(), buffer: Vec::new() }
+    }
+}
+
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = Result<String, Errors>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        self.buffer.clear();
+        match self.reader.read_until(b'\n', &mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => {
+                if self.buffer.ends_with(b"\n") {
+                    self.buffer.pop();
+                    if self.buffer.ends_with(b"\r") {
+                        self.buffer.pop();
+                    }
+                }
+                Some(Ok(String::from_utf8_lossy(&self.buffer).into_owned()))
+            }
+            Err(e) => Some(Err(Errors::io(Operation::Read, &[&self.path], e))),
+        }
+    }
+}
+
+
+impl<R: Read> Chunks<R> {
+    /// Chunks of any reader, `path` being used in the errors. `size` must not be zero.
+    pub fn new(reader: R, path: &Path, size: usize) -> Self {
+        Chunks { reader, path: path.to_path_buf(), size }
+    }
+}
+
+
+impl<R: Read> Iterator for Chunks<R> {
+    type Item = Result<Vec<u8>, Errors>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        let mut chunk = Vec::with_capacity(self.size);
+        match self.reader.by_ref().take(self.size as u64).read_to_end(&mut chunk) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(chunk)),
+            Err(e) => Some(Err(Errors::io(Operation::Read, &[&self.path], e))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::file_system::MemoryFileSystem;
+
+    fn file_system() -> MemoryFileSystem {
+        MemoryFileSystem::new().with_file("/file.txt", "0123456789")
+    }
+
+    #[test]
+    fn reads_ranges_up_to_the_end_of_the_file() {
+        let fs = file_system();
+
+        assert_eq!(FileManager::read_range_on(&fs, "/file.txt", 2, 3).unwrap(), b"234");
+        assert_eq!(FileManager::read_range_on(&fs, "/file.txt", 8, 5).unwrap(), b"89");
+        assert_eq!(FileManager::read_range_on(&fs, "/file.txt", 10, 5).unwrap(), b"");
+        assert_eq!(FileManager::read_range_on(&fs, "/file.txt", 20, 5).unwrap(), b"");
+    }
+
+    #[test]
+    fn reads_ranges_past_the_end_of_a_real_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+
+        assert_eq!(FileManager::read_range(&path, 8, 5).unwrap(), b"89");
+        assert_eq!(FileManager::read_range(&path, 100, 5).unwrap(), b"");
+    }
+
+    #[test]
+    fn splits_files_in_chunks() {
+        let fs = file_system();
+
+        let chunks = FileManager::chunks_on(&fs, "/file.txt", 4).unwrap().collect::<Result<Vec<_>, Errors>>().unwrap();
+
+        assert_eq!(chunks, [b"0123".to_vec(), b"4567".to_vec(), b"89".to_vec()]);
+    }
+
+    #[test]
+    fn refuses_empty_chunks() {
+        let fs = file_system();
+
+        let error = FileManager::chunks_on(&fs, "/file.txt", 0).err().unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn reads_lines_without_their_endings() {
+        let lines = Lines::new(&b"first\r\nsecond\n\xFFthird"[..], Path::new("memory"));
+
+        let lines = lines.collect::<Result<Vec<_>, Errors>>().unwrap();
+
+        assert_eq!(lines, ["first", "second", "\u{FFFD}third"]);
+    }
+}
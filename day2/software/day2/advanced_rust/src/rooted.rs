@@ -0,0 +1,322 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::FileManager;
+use crate::file_system::{FileSystem, Metadata, OsFileSystem, ReadSeek};
+use crate::writing::WriteMode;
+
+
+/// A file manager confined to a directory, for code that must not reach the rest of the disk.
+/// Paths are relative to the root; absolute paths, `..` going above the root and symlinks leading
+/// outside of it fail with `InvalidPath`. It is also a `FileSystem`, to use with the `FileManager::*_on` functions.
+pub struct RootedFileManager {
+    root: PathBuf,
+}
+
+
+impl FileManager {
+    pub fn rooted(root: impl AsRef<Path>) -> Result<RootedFileManager, Errors> {
+
+        RootedFileManager::new(root)
+    }
+}
+
+
+impl RootedFileManager {
+    pub fn new(root: impl AsRef<Path>) -> Result<Self, Errors> {
+
+        let root = root.as_ref();
+        let canonical = fs::canonicalize(root).map_err(|e| Errors::io(Operation::List, &[root], e))?;
+
+        if !canonical.is_dir() {
+            return Err(Errors::new(ErrorKind::NotADirectory, Operation::List, &[root]));
+        }
+        Ok(RootedFileManager { root: canonical })
+    }
+
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+
+    /// Lists the files of a directory, the root itself for an empty path.
+    pub fn list_files(&self, path: impl AsRef<Path>) -> Result<Vec<String>, Errors> {
+
+        let path = path.as_ref();
+        let path = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+
+        FileManager::list_files_on(self, path)
+    }
+
+
+    pub fn read_file(&self, path: impl AsRef<Path>) -> Result<String, Errors> {
+
+        FileManager::read_file_on(self, path)
+    }
+
+
+    pub fn write_file(&self, path: impl AsRef<Path>, content: &str) -> Result<(), Errors> {
+
+        FileManager::write_file_on(self, path, content)
+    }
+
+
+    pub fn copy_file(&self, src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<(), Errors> {
+
+        FileManager::copy_file_on(self, src, dest)
+    }
+
+
+    /// Real location of a path given relative to the root, following the symlinks met on the way.
+    /// The error is an `InvalidInput` one, which `FileManager` reports as `InvalidPath`.
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+
+        let mut resolved = self.root.clone();
+
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => {
+                    resolved.push(part);
+                    if fs::symlink_metadata(&resolved).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+                        // A dangling link cannot be checked, it is refused as well.
+                        resolved = fs::canonicalize(&resolved).map_err(|_| escape(path))?;
+                    }
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(escape(path)),
+            }
+
+            if !resolved.starts_with(&self.root) {
+                return Err(escape(path));
+            }
+        }
+
+        Ok(resolved)
+    }
+
+
+    /// Real location of an entry itself: like `resolve`, except that a symlink at the end of the path
+    /// is not followed, for the calls acting on the link rather than on what it points to.
+    fn resolve_entry(&self, path: &Path) -> io::Result<PathBuf> {
+
+        match (path.parent(), path.components().next_back()) {
+            (Some(parent), Some(Component::Normal(name))) => Ok(self.resolve(parent)?.join(name)),
+            _ => self.resolve(path),
+        }
+    }
+
+
+    /// Like `resolve_entry`, for the calls that would replace or remove the entry: the root itself is refused.
+    fn resolve_child(&self, path: &Path) -> io::Result<PathBuf> {
+
+        let resolved = self.resolve_entry(path)?;
+        if resolved == self.root {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is the root itself", path.display())));
+        }
+        Ok(resolved)
+    }
+}
+
+
+impl FileSystem for RootedFileManager {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let children = OsFileSystem.read_dir(&self.resolve(path)?)?;
+        // Children are given back relative to the root, so that they can be passed in again.
+        Ok(children.into_iter().filter_map(|child| child.file_name().map(|name| path.join(name))).collect())
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        OsFileSystem.symlink_metadata(&self.resolve_entry(path)?)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.resolve(path).is_ok_and(|path| path.is_file())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.resolve(path).is_ok_and(|path| path.is_dir())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        OsFileSystem.read(&self.resolve(path)?)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        OsFileSystem.open(&self.resolve(path)?)
+    }
+
+    fn write_with(&self, path: &Path, content: &[u8], mode: WriteMode) -> io::Result<()> {
+        OsFileSystem.write_with(&self.resolve(path)?, content, mode)
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>> {
+        OsFileSystem.create(&self.resolve(path)?)
+    }
+
+    fn copy(&self, src: &Path, dest: &Path) -> io::Result<u64> {
+        OsFileSystem.copy(&self.resolve(src)?, &self.resolve(dest)?)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        OsFileSystem.create_dir_all(&self.resolve(path)?)
+    }
+
+    fn rename(&self, src: &Path, dest: &Path) -> io::Result<()> {
+        OsFileSystem.rename(&self.resolve_child(src)?, &self.resolve_child(dest)?)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        OsFileSystem.remove_file(&self.resolve_child(path)?)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        OsFileSystem.remove_dir_all(&self.resolve_child(path)?)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        OsFileSystem.read_link(&self.resolve_entry(path)?)
+    }
+
+    /// Only links to a target inside the root are created: one leading outside could otherwise be
+    /// swapped in by another thread for a directory already checked, between a check and its use.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let link = self.resolve_child(link)?;
+        let parent = link.parent().and_then(|parent| parent.strip_prefix(&self.root).ok()).ok_or_else(|| escape(target))?;
+
+        self.resolve(&parent.join(target)).map_err(|_| escape(target))?;
+        OsFileSystem.symlink(target, &link)
+    }
+
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        OsFileSystem.set_permissions(&self.resolve(path)?, mode)
+    }
+
+    fn set_times(&self, path: &Path, accessed: Option<SystemTime>, modified: Option<SystemTime>) -> io::Result<()> {
+        OsFileSystem.set_times(&self.resolve(path)?, accessed, modified)
+    }
+}
+
+
+fn escape(path: &Path) -> io::Error {
+
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{} is outside of the root", path.display()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A root holding `inside/file.txt`, next to `outside/secret.txt`.
+    fn sandbox() -> (TempDir, RootedFileManager) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("root/inside")).unwrap();
+        fs::create_dir_all(dir.path().join("outside")).unwrap();
+        fs::write(dir.path().join("root/inside/file.txt"), "inside").unwrap();
+        fs::write(dir.path().join("outside/secret.txt"), "secret").unwrap();
+
+        let rooted = FileManager::rooted(dir.path().join("root")).unwrap();
+        (dir, rooted)
+    }
+
+    fn assert_escape(result: Result<impl std::fmt::Debug, Errors>) {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidPath);
+    }
+
+    #[test]
+    fn works_inside_the_root() {
+        let (_dir, rooted) = sandbox();
+
+        rooted.write_file("inside/new.txt", "new").unwrap();
+        rooted.copy_file("inside/new.txt", "copy.txt").unwrap();
+
+        assert_eq!(rooted.read_file("inside/../inside/./new.txt").unwrap(), "new");
+        assert_eq!(rooted.list_files("").unwrap(), ["copy.txt"]);
+        assert_eq!(rooted.list_files(".").unwrap(), ["copy.txt"]);
+        assert!(rooted.root().join("copy.txt").is_file());
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        let (dir, rooted) = sandbox();
+
+        assert_escape(rooted.read_file(dir.path().join("outside/secret.txt")));
+        assert_escape(rooted.read_file(rooted.root().join("inside/file.txt")));
+        assert_escape(rooted.list_files("/"));
+    }
+
+    #[test]
+    fn rejects_going_above_the_root() {
+        let (dir, rooted) = sandbox();
+
+        assert_escape(rooted.read_file("../outside/secret.txt"));
+        assert_escape(rooted.read_file("inside/../../outside/secret.txt"));
+        assert_escape(rooted.write_file("../outside/new.txt", "escaped"));
+        assert_escape(rooted.copy_file("inside/file.txt", "../outside/copy.txt"));
+        assert!(!dir.path().join("outside/new.txt").exists());
+        assert!(!dir.path().join("outside/copy.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leading_outside() {
+        use std::os::unix::fs::symlink;
+
+        let (dir, rooted) = sandbox();
+        symlink(dir.path().join("outside"), rooted.root().join("absolute")).unwrap();
+        symlink("../outside/secret.txt", rooted.root().join("relative")).unwrap();
+        symlink("../outside/missing.txt", rooted.root().join("dangling")).unwrap();
+        symlink("inside/file.txt", rooted.root().join("within")).unwrap();
+
+        assert_escape(rooted.read_file("absolute/secret.txt"));
+        assert_escape(rooted.list_files("absolute"));
+        assert_escape(rooted.read_file("relative"));
+        assert_escape(rooted.write_file("dangling", "escaped"));
+        assert!(!dir.path().join("outside/missing.txt").exists());
+
+        assert_eq!(rooted.read_file("within").unwrap(), "inside");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_creates_symlinks_inside_the_root() {
+        let (dir, rooted) = sandbox();
+
+        for target in [dir.path().join("outside"), "/".into(), "../../outside".into(), "../inside/../../outside".into()] {
+            assert_eq!(rooted.symlink(&target, Path::new("inside/link")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(fs::symlink_metadata(rooted.root().join("inside/link")).is_err());
+
+        // A link to the root is fine, going above it through that link is not.
+        rooted.symlink(Path::new(".."), Path::new("inside/up")).unwrap();
+        assert!(rooted.symlink(Path::new("up/../secret"), Path::new("inside/trick")).is_err());
+        assert!(rooted.symlink(Path::new("inside/up/.."), Path::new("trick")).is_err());
+
+        rooted.symlink(Path::new("file.txt"), Path::new("inside/link")).unwrap();
+        rooted.symlink(Path::new("../inside/missing.txt"), Path::new("inside/later")).unwrap();
+        assert_eq!(rooted.read_file("inside/link").unwrap(), "inside");
+        assert_eq!(rooted.read_file("inside/up/inside/file.txt").unwrap(), "inside");
+    }
+
+    #[test]
+    fn refuses_to_remove_or_move_the_root() {
+        let (_dir, rooted) = sandbox();
+
+        for path in ["", ".", "inside/.."] {
+            assert!(rooted.remove_dir_all(Path::new(path)).is_err());
+            assert!(rooted.rename(Path::new(path), Path::new("inside/moved")).is_err());
+            assert!(rooted.rename(Path::new("inside"), Path::new(path)).is_err());
+        }
+        assert!(rooted.root().join("inside/file.txt").is_file());
+
+        rooted.remove_dir_all(Path::new("inside")).unwrap();
+        assert!(!rooted.root().join("inside").exists());
+    }
+}
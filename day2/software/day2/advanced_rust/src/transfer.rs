@@ -0,0 +1,313 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::{CHUNK_SIZE, FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem};
+use crate::listing::{Entry, EntryKind, Listing};
+
+
+/// How far a copy or a move has gone. Symlinks count as files of size zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+}
+
+/// A copy or a move, configured with its builder methods and started with `run`.
+#[must_use = "a transfer does nothing until `run` is called"]
+pub struct Transfer<'a> {
+    fs: &'a dyn FileSystem,
+    operation: Operation,
+    src: PathBuf,
+    dest: PathBuf,
+    preserve_permissions: bool,
+    preserve_timestamps: bool,
+    on_progress: Option<Box<dyn FnMut(Progress)>>,
+}
+
+
+impl FileManager {
+    /// Copies the directory `src` and everything under it to `dest`, created if needed.
+    pub fn copy_dir(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Transfer<'static> {
+
+        Self::copy_dir_on(&OsFileSystem, src, dest)
+    }
+
+
+    /// Moves a file or a directory, renaming it when possible and copying then deleting it
+    /// when `dest` is on another file system. Permissions and timestamps are kept by default.
+    pub fn move_path(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Transfer<'static> {
+
+        Self::move_path_on(&OsFileSystem, src, dest)
+    }
+
+
+    /// Same as `copy_dir`, on the given file system.
+    pub fn copy_dir_on(fs: &impl FileSystem, src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Transfer<'_> {
+
+        Transfer::new(fs, Operation::Copy, src.as_ref(), dest.as_ref(), false)
+    }
+
+
+    /// Same as `move_path`, on the given file system.
+    pub fn move_path_on(fs: &impl FileSystem, src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Transfer<'_> {
+
+        Transfer::new(fs, Operation::Move, src.as_ref(), dest.as_ref(), true)
+    }
+}
+
+
+impl<'a> Transfer<'a> {
+    fn new(fs: &'a dyn FileSystem, operation: Operation, src: &Path, dest: &Path, preserve: bool) -> Self {
+
+        Transfer {
+            fs,
+            operation,
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+            preserve_permissions: preserve,
+            preserve_timestamps: preserve,
+            on_progress: None,
+        }
+    }
+
+    /// Gives the copies the permissions of their source instead of the default ones.
+    pub fn preserve_permissions(mut self, preserve: bool) -> Self {
+        self.preserve_permissions = preserve;
+        self
+    }
+
+    /// Gives the copies the access and modification times of their source.
+    pub fn preserve_timestamps(mut self, preserve: bool) -> Self {
+        self.preserve_timestamps = preserve;
+        self
+    }
+
+    /// Calls `callback` after each chunk copied. Use `move |progress| sender.send(progress)` to report through a channel.
+    /// A move done by a plain rename is instantaneous and reports nothing.
+    pub fn on_progress(mut self, callback: impl FnMut(Progress) + 'static) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+
+    /// Runs the transfer, returning the final progress.
+    pub fn run(mut self) -> Result<Progress, Errors> {
+
+        let (src, dest) = (self.src.clone(), self.dest.clone());
+        check_path(self.operation, &src)?;
+        check_path(self.operation, &dest)?;
+
+        if self.operation == Operation::Move {
+            match self.fs.rename(&src, &dest) {
+                Ok(()) => return Ok(Progress::default()),
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+                Err(e) => return Err(Errors::io(Operation::Move, &[&src, &dest], e)),
+            }
+        }
+
+        let metadata = self.fs.symlink_metadata(&src).map_err(|e| self.error(&src, e))?;
+        let is_dir = metadata.kind == EntryKind::Directory;
+        if self.operation == Operation::Copy && !is_dir {
+            return Err(Errors::new(ErrorKind::NotADirectory, Operation::Copy, &[&src, &dest]));
+        }
+
+        let progress = if is_dir {
+            self.copy_tree(&src, &dest)?
+        } else {
+            let mut progress = Progress { files_total: 1, bytes_total: metadata.len, ..Progress::default() };
+            self.copy_entry(&src, &dest, metadata.kind, &mut progress)?;
+            progress
+        };
+
+        if self.operation == Operation::Move {
+            let removal = if is_dir { self.fs.remove_dir_all(&src) } else { self.fs.remove_file(&src) };
+            removal.map_err(|e| self.error(&src, e))?;
+        }
+
+        Ok(progress)
+    }
+
+
+    fn copy_tree(&mut self, src: &Path, dest: &Path) -> Result<Progress, Errors> {
+
+        // The whole tree is listed first, for the totals and so that a destination inside the
+        // source is not copied into itself over and over.
+        let entries = Listing::new(self.fs, src).recursive().hidden(true).into_iter().collect::<Result<Vec<Entry>, Errors>>()?;
+
+        let files = entries.iter().filter(|entry| matches!(entry.kind, EntryKind::File | EntryKind::Symlink));
+        let mut progress = Progress {
+            files_total: files.clone().count() as u64,
+            bytes_total: files.filter(|entry| entry.kind == EntryKind::File).map(|entry| entry.size).sum(),
+            ..Progress::default()
+        };
+
+        self.fs.create_dir_all(dest).map_err(|e| self.error(dest, e))?;
+
+        for entry in &entries {
+            let target = dest.join(entry.path.strip_prefix(src).unwrap_or(&entry.path));
+            self.copy_entry(&entry.path, &target, entry.kind, &mut progress)?;
+        }
+
+        // Directories get their attributes last, deepest first: copying their content would
+        // update their modification time, and a read-only directory could not be filled.
+        for entry in entries.iter().rev().filter(|entry| entry.kind == EntryKind::Directory) {
+            let target = dest.join(entry.path.strip_prefix(src).unwrap_or(&entry.path));
+            self.preserve(&entry.path, &target)?;
+        }
+        self.preserve(src, dest)?;
+
+        Ok(progress)
+    }
+
+
+    fn copy_entry(&mut self, src: &Path, dest: &Path, kind: EntryKind, progress: &mut Progress) -> Result<(), Errors> {
+
+        match kind {
+            EntryKind::Directory => return self.fs.create_dir_all(dest).map_err(|e| self.error(dest, e)),
+            EntryKind::Symlink => self.copy_symlink(src, dest).map_err(|e| self.error(src, e))?,
+            EntryKind::File => {
+                let mut reader = self.fs.open(src).map_err(|e| self.error(src, e))?;
+                let mut writer = self.fs.create(dest).map_err(|e| self.error(dest, e))?;
+                let mut buffer = vec![0; CHUNK_SIZE];
+
+                loop {
+                    let read = reader.read(&mut buffer).map_err(|e| self.error(src, e))?;
+                    if read == 0 {
+                        break;
+                    }
+                    writer.write_all(&buffer[..read]).map_err(|e| self.error(dest, e))?;
+                    progress.bytes_done += read as u64;
+                    self.report(*progress);
+                }
+                drop(writer);
+                self.preserve(src, dest)?;
+            }
+            // Sockets, fifos and devices are not copied.
+            EntryKind::Other => return Ok(()),
+        }
+
+        progress.files_done += 1;
+        self.report(*progress);
+        Ok(())
+    }
+
+
+    fn preserve(&self, src: &Path, dest: &Path) -> Result<(), Errors> {
+
+        if !self.preserve_permissions && !self.preserve_timestamps {
+            return Ok(());
+        }
+
+        // Only files and directories are given attributes, never symlinks.
+        let metadata = self.fs.symlink_metadata(src).map_err(|e| self.error(src, e))?;
+
+        if self.preserve_timestamps {
+            self.fs.set_times(dest, metadata.accessed, metadata.modified).map_err(|e| self.error(dest, e))?;
+        }
+        if self.preserve_permissions {
+            self.fs.set_permissions(dest, metadata.mode).map_err(|e| self.error(dest, e))?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Recreates the link, or copies the file it points to where there are no symlinks.
+    fn copy_symlink(&self, src: &Path, dest: &Path) -> io::Result<()> {
+
+        match self.fs.read_link(src).and_then(|link| self.fs.symlink(&link, dest)) {
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => self.fs.copy(src, dest).map(|_| ()),
+            copied => copied,
+        }
+    }
+
+
+    fn report(&mut self, progress: Progress) {
+
+        if let Some(callback) = self.on_progress.as_mut() {
+            callback(progress);
+        }
+    }
+
+
+    fn error(&self, path: &Path, source: io::Error) -> Errors {
+
+        Errors::io(self.operation, &[path], source)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// A `src` directory holding 3 files of 10 bytes in all.
+    fn tree() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/sub")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), "12345").unwrap();
+        fs::write(dir.path().join("src/sub/b.txt"), "123").unwrap();
+        fs::write(dir.path().join("src/sub/.c"), "12").unwrap();
+        dir
+    }
+
+    #[test]
+    fn reports_progress_against_the_totals() {
+        let dir = tree();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+
+        let progress = FileManager::copy_dir(dir.path().join("src"), dir.path().join("dest"))
+            .on_progress({
+                let reports = Arc::clone(&reports);
+                move |progress| reports.lock().unwrap().push(progress)
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(progress, Progress { bytes_done: 10, bytes_total: 10, files_done: 3, files_total: 3 });
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.last(), Some(&progress));
+        assert!(reports.iter().all(|report| report.bytes_total == 10 && report.files_total == 3));
+        assert!(reports.windows(2).all(|pair| pair[0].bytes_done <= pair[1].bytes_done && pair[0].files_done <= pair[1].files_done));
+        assert_eq!(fs::read_to_string(dir.path().join("dest/sub/b.txt")).unwrap(), "123");
+    }
+
+    #[test]
+    fn copies_into_a_destination_inside_the_source() {
+        let dir = tree();
+        let src = dir.path().join("src");
+
+        let progress = FileManager::copy_dir(&src, src.join("sub/copy")).run().unwrap();
+
+        assert_eq!(progress.files_done, 3);
+        assert_eq!(fs::read_to_string(src.join("sub/copy/sub/b.txt")).unwrap(), "123");
+        assert!(!src.join("sub/copy/sub/copy").exists());
+    }
+
+    #[test]
+    fn refuses_to_copy_a_file_as_a_directory() {
+        let dir = tree();
+
+        let error = FileManager::copy_dir(dir.path().join("src/a.txt"), dir.path().join("dest")).run().unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::NotADirectory);
+        assert!(!dir.path().join("dest").exists());
+    }
+
+    #[test]
+    fn moves_a_directory() {
+        let dir = tree();
+
+        FileManager::move_path(dir.path().join("src"), dir.path().join("moved")).run().unwrap();
+
+        assert!(!dir.path().join("src").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("moved/a.txt")).unwrap(), "12345");
+    }
+}
@@ -0,0 +1,347 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+
+
+/// How long a path must stay quiet before its event is delivered, unless set with `Watch::debounce`.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Longest time the delivery thread waits before checking whether it was stopped.
+const TICK: Duration = Duration::from_millis(50);
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+/// A watch to start, configured with its builder methods.
+pub struct Watch {
+    path: PathBuf,
+    recursive: bool,
+    debounce: Duration,
+}
+
+/// A running watch. Events stop being delivered once it is stopped or dropped.
+pub struct Watcher {
+    watcher: Option<RecommendedWatcher>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// An event waiting for its path to stay quiet.
+struct Pending {
+    event: WatchEvent,
+    at: Instant,
+}
+
+
+impl FileManager {
+    /// Watches a file, or the direct content of a directory unless `recursive` is used.
+    pub fn watch(path: impl AsRef<Path>) -> Watch {
+
+        Watch { path: path.as_ref().to_path_buf(), recursive: false, debounce: DEFAULT_DEBOUNCE }
+    }
+}
+
+
+impl Watch {
+    /// Also watches every subdirectory, including the ones created later.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Waits for a path to stay quiet this long before delivering its event, merging the changes made meanwhile.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+
+    /// Starts watching, calling `callback` from a background thread for each event.
+    pub fn on_event(self, mut callback: impl FnMut(WatchEvent) + Send + 'static) -> Result<Watcher, Errors> {
+
+        check_path(Operation::Watch, &self.path)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| to_errors(e, &self.path))?;
+        let mode = if self.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        watcher.watch(&self.path, mode).map_err(|e| to_errors(e, &self.path))?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stopped = Arc::clone(&stopped);
+            let debounce = self.debounce;
+            thread::spawn(move || deliver(receiver, debounce, &stopped, &mut callback))
+        };
+
+        Ok(Watcher { watcher: Some(watcher), stopped, thread: Some(thread) })
+    }
+
+
+    /// Starts watching, sending the events to the returned receiver.
+    pub fn channel(self) -> Result<(Watcher, Receiver<WatchEvent>), Errors> {
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = self.on_event(move |event| {
+            // The receiver being gone only means nobody listens anymore.
+            let _ = sender.send(event);
+        })?;
+        Ok((watcher, receiver))
+    }
+}
+
+
+impl Watcher {
+    /// Stops watching and waits for the delivery thread to end. Events still being debounced are dropped.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+
+fn deliver(receiver: Receiver<notify::Result<notify::Event>>, debounce: Duration, stopped: &AtomicBool, callback: &mut impl FnMut(WatchEvent)) {
+
+    let mut pending: Vec<Pending> = Vec::new();
+
+    while !stopped.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(wait(&pending, debounce, Instant::now())) {
+            // Errors of the underlying watcher carry no path to report, they are skipped.
+            Ok(Ok(event)) => {
+                for event in translate(event) {
+                    merge(&mut pending, event);
+                }
+            }
+            Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        while let Some(index) = pending.iter().position(|pending| now.duration_since(pending.at) >= debounce) {
+            if stopped.load(Ordering::SeqCst) {
+                return;
+            }
+            callback(pending.remove(index).event);
+        }
+    }
+}
+
+
+/// How long to wait for more events: a tick while nothing is pending, so that stopping is noticed,
+/// and until the earliest pending event is due otherwise.
+fn wait(pending: &[Pending], debounce: Duration, now: Instant) -> Duration {
+
+    pending.iter()
+        .map(|pending| (pending.at + debounce).saturating_duration_since(now))
+        .min()
+        .map_or(TICK, |remaining| remaining.min(TICK))
+}
+
+
+/// Turns a notify event into the events of this API, ignoring accesses.
+fn translate(event: notify::Event) -> Vec<WatchEvent> {
+
+    let mut paths = event.paths.into_iter();
+
+    match event.kind {
+        EventKind::Create(_) => paths.map(WatchEvent::Created).collect(),
+        EventKind::Remove(_) => paths.map(WatchEvent::Removed).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match (paths.next(), paths.next()) {
+            (Some(from), Some(to)) => vec![WatchEvent::Renamed { from, to }],
+            _ => Vec::new(),
+        },
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.map(WatchEvent::Removed).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.map(WatchEvent::Created).collect(),
+        // Renames whose direction is unknown are told apart by whether the path still exists.
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .map(|path| if path.exists() { WatchEvent::Created(path) } else { WatchEvent::Removed(path) })
+            .collect(),
+        EventKind::Modify(_) | EventKind::Any | EventKind::Other => paths.map(WatchEvent::Modified).collect(),
+        EventKind::Access(_) => Vec::new(),
+    }
+}
+
+
+/// Adds an event to the pending ones, merging it with an earlier event on the same path.
+fn merge(pending: &mut Vec<Pending>, event: WatchEvent) {
+
+    let at = Instant::now();
+    let path = match &event {
+        WatchEvent::Created(path) | WatchEvent::Modified(path) | WatchEvent::Removed(path) => path.clone(),
+        WatchEvent::Renamed { from, to } => {
+            // Some platforms report both halves of a rename before the rename itself.
+            let (removed, created) = (WatchEvent::Removed(from.clone()), WatchEvent::Created(to.clone()));
+            pending.retain(|pending| pending.event != removed && pending.event != created);
+            pending.push(Pending { event, at });
+            return;
+        }
+    };
+
+    let Some(index) = pending.iter().position(|pending| !matches!(pending.event, WatchEvent::Renamed { .. }) && path_of(&pending.event) == path) else {
+        pending.push(Pending { event, at });
+        return;
+    };
+
+    let merged = match (&pending[index].event, &event) {
+        (WatchEvent::Created(_), WatchEvent::Removed(_)) => None,
+        (WatchEvent::Created(_), _) => Some(WatchEvent::Created(path)),
+        (WatchEvent::Removed(_), WatchEvent::Created(_)) => Some(WatchEvent::Modified(path)),
+        (_, WatchEvent::Removed(_)) => Some(WatchEvent::Removed(path)),
+        _ => Some(WatchEvent::Modified(path)),
+    };
+
+    pending.remove(index);
+    if let Some(event) = merged {
+        pending.push(Pending { event, at });
+    }
+}
+
+
+fn path_of(event: &WatchEvent) -> &Path {
+
+    match event {
+        WatchEvent::Created(path) | WatchEvent::Modified(path) | WatchEvent::Removed(path) => path,
+        WatchEvent::Renamed { to, .. } => to,
+    }
+}
+
+
+fn to_errors(error: notify::Error, path: &Path) -> Errors {
+
+    match error.kind {
+        notify::ErrorKind::Io(e) => Errors::io(Operation::Watch, &[path], e),
+        notify::ErrorKind::PathNotFound => Errors::new(ErrorKind::FileNotFound, Operation::Watch, &[path]),
+        _ => Errors::new(ErrorKind::Other, Operation::Watch, &[path]),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn watch(path: &Path, recursive: bool) -> (Watcher, Receiver<WatchEvent>) {
+        FileManager::watch(path).recursive(recursive).debounce(Duration::from_millis(100)).channel().unwrap()
+    }
+
+    #[test]
+    fn reports_created_modified_renamed_and_removed_files() {
+        let dir = tempdir().unwrap();
+        let (watcher, events) = watch(dir.path(), false);
+        let file = dir.path().join("file.txt");
+        let renamed = dir.path().join("renamed.txt");
+
+        fs::write(&file, "created").unwrap();
+        assert_eq!(events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Created(file.clone()));
+
+        fs::write(&file, "modified").unwrap();
+        assert_eq!(events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Modified(file.clone()));
+
+        fs::rename(&file, &renamed).unwrap();
+        assert_eq!(events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Renamed { from: file, to: renamed.clone() });
+
+        fs::remove_file(&renamed).unwrap();
+        assert_eq!(events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Removed(renamed));
+
+        watcher.stop();
+    }
+
+    #[test]
+    fn merges_changes_made_within_the_debounce_delay() {
+        let dir = tempdir().unwrap();
+        let (_watcher, events) = watch(dir.path(), false);
+        let file = dir.path().join("file.txt");
+        let temporary = dir.path().join("temporary.txt");
+
+        fs::write(&temporary, "short lived").unwrap();
+        fs::remove_file(&temporary).unwrap();
+        for content in ["one", "two", "three"] {
+            fs::write(&file, content).unwrap();
+        }
+
+        assert_eq!(events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Created(file));
+        assert!(events.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn watches_subdirectories_only_when_recursive() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        fs::create_dir(&nested).unwrap();
+        let file = nested.join("file.txt");
+
+        let (flat, flat_events) = watch(dir.path(), false);
+        let (recursive, recursive_events) = watch(dir.path(), true);
+        fs::write(&file, "deep").unwrap();
+
+        assert_eq!(recursive_events.recv_timeout(TIMEOUT).unwrap(), WatchEvent::Created(file));
+        // The directory itself may be reported as modified, but not the file inside it.
+        assert!(flat_events.try_iter().all(|event| event == WatchEvent::Modified(nested.clone())));
+
+        flat.stop();
+        recursive.stop();
+    }
+
+    #[test]
+    fn stops_delivering_events_once_stopped() {
+        let dir = tempdir().unwrap();
+        let (watcher, events) = watch(dir.path(), false);
+
+        watcher.stop();
+        fs::write(dir.path().join("file.txt"), "unseen").unwrap();
+
+        assert_eq!(events.recv_timeout(Duration::from_millis(500)), Err(mpsc::RecvTimeoutError::Disconnected));
+    }
+
+    #[test]
+    fn fails_on_missing_path() {
+        let dir = tempdir().unwrap();
+
+        let error = FileManager::watch(dir.path().join("missing")).channel().err().unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::FileNotFound);
+        assert_eq!(error.operation(), Some(Operation::Watch));
+    }
+
+    #[test]
+    fn waits_a_tick_while_nothing_is_pending() {
+        let now = Instant::now();
+        let pending = |ago: u64| Pending { event: WatchEvent::Modified(PathBuf::from("file")), at: now - Duration::from_millis(ago) };
+
+        assert_eq!(wait(&[], Duration::ZERO, now), TICK);
+        assert_eq!(wait(&[], Duration::from_secs(1), now), TICK);
+        assert_eq!(wait(&[pending(10)], Duration::from_secs(1), now), TICK);
+        assert_eq!(wait(&[pending(990), pending(10)], Duration::from_secs(1), now), Duration::from_millis(10));
+        assert_eq!(wait(&[pending(10)], Duration::ZERO, now), Duration::ZERO);
+    }
+}
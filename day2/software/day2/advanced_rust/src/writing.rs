@@ -0,0 +1,168 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::errors::{ErrorKind, Errors, Operation};
+use crate::file_manager::{FileManager, check_path};
+use crate::file_system::{FileSystem, OsFileSystem};
+
+
+/// Distinguishes the temporary files of concurrent atomic writes within the process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Replaces the content of the file, creating it if needed, like `write_file`.
+    Truncate,
+    /// Adds the content at the end of the file, creating it if needed.
+    Append,
+    /// Creates the file, failing with `AlreadyExists` if it is already there.
+    CreateNew,
+    /// Replaces the file so that it holds either its old or its new content, even after a crash:
+    /// the content goes to a temporary file of the same directory, synced, then renamed over the target.
+    Atomic,
+}
+
+
+impl FileManager {
+    pub fn write_file_with(path: impl AsRef<Path>, content: impl AsRef<[u8]>, mode: WriteMode) -> Result<(), Errors> {
+
+        Self::write_file_with_on(&OsFileSystem, path, content, mode)
+    }
+
+
+    /// Same as `write_file_with`, on the given file system.
+    pub fn write_file_with_on(fs: &impl FileSystem, path: impl AsRef<Path>, content: impl AsRef<[u8]>, mode: WriteMode) -> Result<(), Errors> {
+
+        let path = path.as_ref();
+        check_path(Operation::Write, path)?;
+
+        if mode == WriteMode::Atomic && path.file_name().is_none() {
+            return Err(Errors::new(ErrorKind::InvalidPath, Operation::Write, &[path]));
+        }
+
+        fs.write_with(path, content.as_ref(), mode).map_err(|e| Errors::io(Operation::Write, &[path], e))
+    }
+}
+
+
+/// `WriteMode::Atomic` on the real file system. `path` must have a file name.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+
+    let name = path.file_name().ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let temp = directory.join(format!(
+        ".{}.{}.{}.tmp",
+        name.to_string_lossy(),
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = (|| -> io::Result<()> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&temp)?;
+        file.write_all(content)?;
+        // The replaced file keeps its permissions.
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    sync_directory(&directory)
+}
+
+
+/// Makes the rename itself durable.
+#[cfg(unix)]
+fn sync_directory(directory: &Path) -> io::Result<()> {
+
+    File::open(directory)?.sync_all()
+}
+
+
+/// Directories cannot be opened as files on other platforms, the rename is left to the file system.
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) -> io::Result<()> {
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_new_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+
+        FileManager::write_file_with(&path, "first", WriteMode::CreateNew).unwrap();
+        let error = FileManager::write_file_with(&path, "second", WriteMode::CreateNew).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+    }
+
+    #[test]
+    fn appends_and_truncates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+
+        FileManager::write_file_with(&path, "a", WriteMode::Append).unwrap();
+        FileManager::write_file_with(&path, "b", WriteMode::Append).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ab");
+
+        FileManager::write_file_with(&path, "c", WriteMode::Truncate).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "c");
+    }
+
+    #[test]
+    fn writes_atomically_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+
+        FileManager::write_file_with(&path, "old", WriteMode::Atomic).unwrap();
+        FileManager::write_file_with(&path, "new", WriteMode::Atomic).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_writes_keep_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+
+        FileManager::write_file_with(&path, "new", WriteMode::Atomic).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o751);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    }
+
+    #[test]
+    fn refuses_atomic_writes_without_a_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let error = FileManager::write_file_with(dir.path().join(".."), "content", WriteMode::Atomic).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidPath);
+    }
+}